@@ -0,0 +1,399 @@
+use crate::controllers::controller::Controller;
+use crate::entities::{ball::Ball, side::Side};
+use crate::rng::{self, GameRng};
+use crate::simulation::{PaddleInput, Simulation};
+use ggez::glam::Vec2;
+use rand::Rng;
+use std::fmt;
+use std::str::FromStr;
+
+// Closer than this to its target, the paddle stays put instead of twitching
+const TARGET_TOLERANCE: f32 = 1.0;
+
+// Furthest from the paddle's center the computer tries to hit the ball when aiming,
+// as a fraction of half the paddle's height, so small misses still connect
+const MAX_AIM_OFFSET: f32 = 0.8;
+
+/// How well the computer plays
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Medium,
+    Hard,
+}
+
+/// What a difficulty level changes about the computer's play
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DifficultySettings {
+    pub reaction_delay: f32,    // Seconds between looks at the court
+    pub prediction_error: f32,  // Largest miss in its guess of where a ball arrives
+    pub max_speed: f32,         // Fraction of the paddle's top speed it uses
+    pub chases_power_ups: bool, // Whether it aims its returns through power-ups
+}
+
+impl Difficulty {
+    pub fn settings(self) -> DifficultySettings {
+        match self {
+            Difficulty::Easy => DifficultySettings {
+                reaction_delay: 0.35,
+                prediction_error: 110.0,
+                max_speed: 0.55,
+                chases_power_ups: false,
+            },
+            Difficulty::Medium => DifficultySettings {
+                reaction_delay: 0.2,
+                prediction_error: 35.0,
+                max_speed: 0.85,
+                chases_power_ups: false,
+            },
+            Difficulty::Hard => DifficultySettings {
+                reaction_delay: 0.08,
+                prediction_error: 8.0,
+                max_speed: 1.0,
+                chases_power_ups: true,
+            },
+        }
+    }
+}
+
+impl fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Difficulty::Easy => "Easy",
+            Difficulty::Medium => "Medium",
+            Difficulty::Hard => "Hard",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+// Reads the names used on the command line
+impl FromStr for Difficulty {
+    type Err = ();
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        match text {
+            "easy" => Ok(Difficulty::Easy),
+            "medium" => Ok(Difficulty::Medium),
+            "hard" => Ok(Difficulty::Hard),
+            _ => Err(()),
+        }
+    }
+}
+
+/// A computer opponent. It works out where the next ball will reach its paddle, bounces off
+/// the walls included, and moves there as well as its difficulty allows.
+pub struct ComputerPlayer {
+    pub difficulty: Difficulty,
+    rng: GameRng, // Its own RNG, so its mistakes never change the match's random draws
+    until_look: f32, // Seconds until it next looks at the court
+    target_y: f32, // Where it is moving the paddle's center
+    error: f32,   // How far off its guess is for the ball it is tracking
+    ball_incoming: bool, // Whether a ball was heading its way when it last looked
+}
+
+impl ComputerPlayer {
+    // Creates a computer player whose mistakes are fully determined by `seed`
+    pub fn new(difficulty: Difficulty, seed: u64) -> Self {
+        Self {
+            difficulty,
+            rng: rng::seeded(seed),
+            until_look: 0.0,
+            target_y: f32::NAN,
+            error: 0.0,
+            ball_incoming: false,
+        }
+    }
+
+    // Picks where the paddle's center should go, based on the balls as they are now
+    fn plan(&mut self, simulation: &Simulation, side: Side, settings: &DifficultySettings) -> f32 {
+        let paddle = simulation.player(side).rect();
+        let court_height = simulation.game_config.court.height;
+
+        // The ball that reaches the paddle first matters most during multiball
+        let face_x = match side {
+            Side::Left => paddle.x + paddle.w,
+            Side::Right => paddle.x,
+        };
+        let next_arrival = simulation
+            .balls
+            .iter()
+            .filter(|ball| ball.hold.is_none())
+            .filter_map(|ball| predict_arrival(ball, face_x, court_height).map(|a| (ball, a)))
+            .min_by(|(_, a), (_, b)| a.time.total_cmp(&b.time));
+
+        let Some((ball, arrival)) = next_arrival else {
+            // Nothing coming, so wait in the middle
+            self.ball_incoming = false;
+            return court_height / 2.0;
+        };
+
+        // A new guess for each ball coming its way, good or bad depending on the difficulty
+        if !self.ball_incoming && settings.prediction_error > 0.0 {
+            self.error = self
+                .rng
+                .random_range(-settings.prediction_error..settings.prediction_error);
+        }
+        self.ball_incoming = true;
+        let arrival_y = arrival.position.y + self.error;
+
+        // Hit the ball off-center to send it through a power-up
+        let aim = if settings.chases_power_ups {
+            aim_at_power_up(simulation, ball, &arrival, face_x)
+        } else {
+            0.0
+        };
+
+        arrival_y - aim * paddle.h / 2.0
+    }
+}
+
+impl Controller for ComputerPlayer {
+    fn command(&mut self, simulation: &Simulation, side: Side, delta_time: f32) -> PaddleInput {
+        let settings = self.difficulty.settings();
+        let paddle = simulation.player(side);
+        let paddle_center = paddle.position.y + paddle.size.y / 2.0;
+
+        // Only react to what it saw when it last looked
+        self.until_look -= delta_time;
+        if self.until_look <= 0.0 || self.target_y.is_nan() {
+            self.until_look = settings.reaction_delay;
+            self.target_y = self.plan(simulation, side, &settings);
+        }
+
+        // Head for the target without overshooting it
+        let distance = self.target_y - paddle_center;
+        let full_step = paddle.max_speed() * delta_time;
+        let movement = if distance.abs() < TARGET_TOLERANCE || full_step <= 0.0 {
+            0.0
+        } else {
+            (distance / full_step).clamp(-settings.max_speed, settings.max_speed)
+        };
+
+        // Serve once it has had time to notice it is holding the ball
+        let release = simulation.balls.iter().any(|ball| {
+            ball.hold
+                .is_some_and(|hold| hold.side == side && hold.time >= settings.reaction_delay)
+        });
+
+        PaddleInput {
+            movement,
+            target: None,
+            release,
+        }
+    }
+
+    fn is_computer(&self) -> bool {
+        true
+    }
+}
+
+// Where and when a ball will reach a paddle face
+struct Arrival {
+    time: f32,
+    position: Vec2,
+    velocity: Vec2,
+}
+
+// Follows a ball's path, bouncing off the top and bottom walls, to where its edge reaches the
+// paddle face at `face_x`. Returns None for a ball heading the other way.
+fn predict_arrival(ball: &Ball, face_x: f32, court_height: f32) -> Option<Arrival> {
+    let target_x = if ball.velocity.x < 0.0 {
+        face_x + ball.radius
+    } else {
+        face_x - ball.radius
+    };
+    let time = (target_x - ball.position.x) / ball.velocity.x;
+    if !time.is_finite() || time < 0.0 {
+        return None;
+    }
+
+    // Unfold the bounces: the path is straight in a mirrored court, folded back into the real one
+    let top = ball.radius;
+    let span = (court_height - 2.0 * ball.radius).max(f32::EPSILON);
+    let unfolded = (ball.position.y + ball.velocity.y * time - top).rem_euclid(2.0 * span);
+    let (y, velocity_y) = if unfolded > span {
+        (top + 2.0 * span - unfolded, -ball.velocity.y)
+    } else {
+        (top + unfolded, ball.velocity.y)
+    };
+
+    Some(Arrival {
+        time,
+        position: Vec2::new(target_x, y),
+        velocity: Vec2::new(ball.velocity.x, velocity_y),
+    })
+}
+
+// Works out where on the paddle to hit the ball so it flies straight through the closest
+// power-up on the court, as a fraction of half the paddle's height from its center.
+// Returns 0 (a plain center hit) when no power-up can be reached that way.
+fn aim_at_power_up(simulation: &Simulation, ball: &Ball, arrival: &Arrival, face_x: f32) -> f32 {
+    let spin_speed = simulation.game_config.ball.speed * 0.5;
+    if spin_speed <= 0.0 {
+        return 0.0;
+    }
+
+    let power_up = simulation
+        .power_up_manager
+        .power_ups()
+        .iter()
+        .filter(|power_up| power_up.active)
+        .min_by(|a, b| {
+            let distance = |x: f32| (x - face_x).abs();
+            distance(a.position.x).total_cmp(&distance(b.position.x))
+        });
+    let Some(power_up) = power_up else {
+        return 0.0;
+    };
+
+    // The return keeps the ball's horizontal speed, and the hit's offset from the paddle's
+    // center adds vertical speed
+    let across = (power_up.position.x - arrival.position.x).abs();
+    if across <= ball.radius {
+        return 0.0;
+    }
+    let wanted_velocity_y =
+        (power_up.position.y - arrival.position.y) / across * arrival.velocity.x.abs();
+    let aim = (wanted_velocity_y - arrival.velocity.y) / spin_speed;
+
+    if aim.abs() > MAX_AIM_OFFSET {
+        0.0
+    } else {
+        aim
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::GameConfig;
+    use crate::match_config::MatchConfig;
+    use crate::powerups::powerup::PowerUp;
+    use crate::simulation::{FrameInput, GameEvent};
+    use std::time::Duration;
+
+    // A simulation with its first ball already in flight. Power-ups never spawn, so only the
+    // walls and paddles change the ball's path.
+    fn simulation_with_ball(position: Vec2, angle: f32) -> Simulation {
+        let mut game_config = GameConfig::default();
+        game_config.power_ups.spawn_interval = 1000.0;
+        let mut simulation = Simulation::new(game_config, MatchConfig::default(), 1);
+        let ball = &mut simulation.balls[0];
+        ball.hold = None;
+        ball.position = position;
+        ball.velocity = Vec2::from_angle(angle) * game_config.ball.speed;
+        simulation
+    }
+
+    fn right_face(simulation: &Simulation) -> f32 {
+        simulation.simba_player.rect().x
+    }
+
+    fn tick(simulation: &Simulation) -> Duration {
+        Duration::from_secs_f64(1.0 / f64::from(simulation.game_config.simulation.tick_rate))
+    }
+
+    // Steps the simulation until the ball's edge reaches the paddle face at `face_x`. Returns
+    // where the ball's center was then and how many times it bounced off a wall on the way.
+    fn simulate_arrival(simulation: &mut Simulation, face_x: f32) -> (Vec2, usize) {
+        let target_x = face_x - simulation.balls[0].radius;
+        let mut wall_hits = 0;
+        for _ in 0..10_000 {
+            let before = simulation.balls[0].position;
+            let events = simulation.step(tick(simulation), &FrameInput::default());
+            wall_hits += events
+                .iter()
+                .filter(|event| matches!(event, GameEvent::WallHit { .. }))
+                .count();
+
+            let paddle_hit = events.iter().find_map(|event| match event {
+                GameEvent::PaddleHit { position, .. } => Some(*position),
+                _ => None,
+            });
+            if let Some(position) = paddle_hit {
+                return (Vec2::new(target_x, position.y), wall_hits);
+            }
+            let after = simulation.balls[0].position;
+            if after.x >= target_x {
+                let t = (target_x - before.x) / (after.x - before.x);
+                return (before.lerp(after, t), wall_hits);
+            }
+        }
+        panic!("the ball never reached the paddle");
+    }
+
+    // Checks the prediction for a ball against where the simulation actually takes it
+    fn assert_prediction_matches(position: Vec2, angle: f32, expected_wall_hits: usize) {
+        let mut simulation = simulation_with_ball(position, angle);
+        let face_x = right_face(&simulation);
+        let court_height = simulation.game_config.court.height;
+        let arrival = predict_arrival(&simulation.balls[0], face_x, court_height).unwrap();
+
+        let (actual, wall_hits) = simulate_arrival(&mut simulation, face_x);
+        assert_eq!(wall_hits, expected_wall_hits);
+        assert!(
+            (arrival.position.y - actual.y).abs() < 1.0,
+            "predicted y {}, the ball arrived at {}",
+            arrival.position.y,
+            actual.y
+        );
+    }
+
+    #[test]
+    fn predicts_a_direct_shot() {
+        assert_prediction_matches(Vec2::new(500.0, 350.0), 0.2, 0);
+    }
+
+    #[test]
+    fn predicts_a_shot_off_one_wall() {
+        assert_prediction_matches(Vec2::new(500.0, 600.0), 0.5, 1);
+    }
+
+    #[test]
+    fn predicts_a_shot_off_both_walls() {
+        assert_prediction_matches(Vec2::new(500.0, 350.0), 1.3, 2);
+    }
+
+    #[test]
+    fn ignores_a_ball_heading_away() {
+        let simulation = simulation_with_ball(Vec2::new(500.0, 350.0), 3.0);
+        let court_height = simulation.game_config.court.height;
+        let face_x = right_face(&simulation);
+        assert!(predict_arrival(&simulation.balls[0], face_x, court_height).is_none());
+    }
+
+    #[test]
+    fn aims_returns_through_a_power_up() {
+        let mut simulation = simulation_with_ball(Vec2::new(500.0, 350.0), 0.2);
+        let definition = &simulation.power_up_manager.definitions()[0];
+        let power_up = PowerUp::new(Vec2::new(700.0, 420.0), 0, definition.color, 20.0);
+        simulation.power_up_manager.place(power_up);
+
+        let face_x = right_face(&simulation);
+        let court_height = simulation.game_config.court.height;
+        let ball = &simulation.balls[0];
+        let arrival = predict_arrival(ball, face_x, court_height).unwrap();
+        let aim = aim_at_power_up(&simulation, ball, &arrival, face_x);
+        assert!(aim != 0.0 && aim.abs() <= MAX_AIM_OFFSET);
+
+        // Put the paddle where the computer would and let the return play out
+        let paddle = &mut simulation.simba_player;
+        paddle.position.y = arrival.position.y - aim * paddle.size.y / 2.0 - paddle.size.y / 2.0;
+        for _ in 0..240 {
+            simulation.step(tick(&simulation), &FrameInput::default());
+        }
+        assert!(simulation.power_up_manager.power_ups().is_empty());
+    }
+
+    #[test]
+    fn hits_the_center_with_no_power_up_to_aim_at() {
+        let simulation = simulation_with_ball(Vec2::new(500.0, 350.0), 0.2);
+        let face_x = right_face(&simulation);
+        let court_height = simulation.game_config.court.height;
+        let ball = &simulation.balls[0];
+        let arrival = predict_arrival(ball, face_x, court_height).unwrap();
+        assert_eq!(aim_at_power_up(&simulation, ball, &arrival, face_x), 0.0);
+    }
+}
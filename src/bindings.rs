@@ -0,0 +1,475 @@
+use crate::config::ConfigError;
+use crate::entities::side::Side;
+use ggez::input::keyboard::KeyCode;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::Path;
+
+/// File the controls are saved to, in the user's config directory
+pub const CONTROLS_FILE_NAME: &str = "controls.toml";
+
+/// Something the player can do with a key
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    LeftUp,
+    LeftDown,
+    LeftServe,
+    RightUp,
+    RightDown,
+    RightServe,
+    Pause,
+    Restart,
+    CycleMode,
+    Fullscreen,
+}
+
+impl Action {
+    /// Every action, in the order the controls screen lists them
+    pub const ALL: [Action; 10] = [
+        Action::LeftUp,
+        Action::LeftDown,
+        Action::LeftServe,
+        Action::RightUp,
+        Action::RightDown,
+        Action::RightServe,
+        Action::Pause,
+        Action::Restart,
+        Action::CycleMode,
+        Action::Fullscreen,
+    ];
+
+    /// Returns a description of the action
+    pub fn description(&self) -> &'static str {
+        match self {
+            Action::LeftUp => "Yanga up",
+            Action::LeftDown => "Yanga down",
+            Action::LeftServe => "Yanga serve",
+            Action::RightUp => "Simba up",
+            Action::RightDown => "Simba down",
+            Action::RightServe => "Simba serve",
+            Action::Pause => "Pause",
+            Action::Restart => "Restart",
+            Action::CycleMode => "Change game mode",
+            Action::Fullscreen => "Fullscreen",
+        }
+    }
+
+    // The up, down and serve actions of one side's paddle
+    pub fn paddle_actions(side: Side) -> [Action; 3] {
+        match side {
+            Side::Left => [Action::LeftUp, Action::LeftDown, Action::LeftServe],
+            Side::Right => [Action::RightUp, Action::RightDown, Action::RightServe],
+        }
+    }
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.description())
+    }
+}
+
+/// The keys bound to each action. An action can have several keys, or none.
+///
+/// Saved as TOML with a list of key names per action. Actions missing from the file keep
+/// their default keys.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Bindings {
+    #[serde(with = "key_names")]
+    pub left_up: Vec<KeyCode>,
+    #[serde(with = "key_names")]
+    pub left_down: Vec<KeyCode>,
+    #[serde(with = "key_names")]
+    pub left_serve: Vec<KeyCode>,
+    #[serde(with = "key_names")]
+    pub right_up: Vec<KeyCode>,
+    #[serde(with = "key_names")]
+    pub right_down: Vec<KeyCode>,
+    #[serde(with = "key_names")]
+    pub right_serve: Vec<KeyCode>,
+    #[serde(with = "key_names")]
+    pub pause: Vec<KeyCode>,
+    #[serde(with = "key_names")]
+    pub restart: Vec<KeyCode>,
+    #[serde(with = "key_names")]
+    pub cycle_mode: Vec<KeyCode>,
+    #[serde(with = "key_names")]
+    pub fullscreen: Vec<KeyCode>,
+}
+
+impl Default for Bindings {
+    fn default() -> Self {
+        Self {
+            left_up: vec![KeyCode::W],
+            left_down: vec![KeyCode::S],
+            left_serve: vec![KeyCode::D],
+            right_up: vec![KeyCode::Up],
+            right_down: vec![KeyCode::Down],
+            right_serve: vec![KeyCode::Left],
+            pause: vec![KeyCode::Space],
+            restart: vec![KeyCode::R],
+            cycle_mode: vec![KeyCode::M],
+            fullscreen: vec![KeyCode::F11],
+        }
+    }
+}
+
+impl Bindings {
+    /// Reads bindings from a TOML file
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ConfigError> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path).map_err(|source| ConfigError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+
+        toml::from_str(&text).map_err(|error| ConfigError::Parse {
+            path: Some(path.to_path_buf()),
+            message: error.to_string(),
+        })
+    }
+
+    /// Writes the bindings to a TOML file, creating its directory if needed
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), ConfigError> {
+        let path = path.as_ref();
+        let write_error = |source| ConfigError::Write {
+            path: path.to_path_buf(),
+            source,
+        };
+
+        if let Some(directory) = path.parent() {
+            std::fs::create_dir_all(directory).map_err(write_error)?;
+        }
+        std::fs::write(path, self.to_toml()).map_err(write_error)
+    }
+
+    /// The bindings as TOML
+    pub fn to_toml(&self) -> String {
+        toml::to_string_pretty(self).unwrap_or_default()
+    }
+
+    pub fn keys(&self, action: Action) -> &[KeyCode] {
+        match action {
+            Action::LeftUp => &self.left_up,
+            Action::LeftDown => &self.left_down,
+            Action::LeftServe => &self.left_serve,
+            Action::RightUp => &self.right_up,
+            Action::RightDown => &self.right_down,
+            Action::RightServe => &self.right_serve,
+            Action::Pause => &self.pause,
+            Action::Restart => &self.restart,
+            Action::CycleMode => &self.cycle_mode,
+            Action::Fullscreen => &self.fullscreen,
+        }
+    }
+
+    fn keys_mut(&mut self, action: Action) -> &mut Vec<KeyCode> {
+        match action {
+            Action::LeftUp => &mut self.left_up,
+            Action::LeftDown => &mut self.left_down,
+            Action::LeftServe => &mut self.left_serve,
+            Action::RightUp => &mut self.right_up,
+            Action::RightDown => &mut self.right_down,
+            Action::RightServe => &mut self.right_serve,
+            Action::Pause => &mut self.pause,
+            Action::Restart => &mut self.restart,
+            Action::CycleMode => &mut self.cycle_mode,
+            Action::Fullscreen => &mut self.fullscreen,
+        }
+    }
+
+    // Whether `key` triggers `action`
+    pub fn is_bound(&self, action: Action, key: KeyCode) -> bool {
+        self.keys(action).contains(&key)
+    }
+
+    // Adds `key` to an action's keys. A key only does one thing, so it is taken away from
+    // any other action first. Keys without a saved name are refused, since they couldn't be
+    // written to the controls file; returns whether the key was bound.
+    pub fn bind(&mut self, action: Action, key: KeyCode) -> bool {
+        if saved_name(key).is_none() {
+            return false;
+        }
+        for other in Action::ALL {
+            self.keys_mut(other).retain(|&bound| bound != key);
+        }
+        self.keys_mut(action).push(key);
+        true
+    }
+
+    // Leaves an action with no keys
+    pub fn clear(&mut self, action: Action) {
+        self.keys_mut(action).clear();
+    }
+}
+
+/// The name a key is saved under, which is also how it is shown on screen
+pub fn key_name(key: KeyCode) -> &'static str {
+    saved_name(key).unwrap_or("?")
+}
+
+// The name a key is saved under, if it has one
+fn saved_name(key: KeyCode) -> Option<&'static str> {
+    KEY_NAMES
+        .iter()
+        .find(|(code, _)| *code == key)
+        .map(|(_, name)| *name)
+}
+
+/// Looks up a key by the name it is saved under
+pub fn parse_key(name: &str) -> Option<KeyCode> {
+    KEY_NAMES
+        .iter()
+        .find(|(_, key_name)| *key_name == name)
+        .map(|(code, _)| *code)
+}
+
+// Saves key lists as key names
+mod key_names {
+    use super::{parse_key, saved_name};
+    use ggez::input::keyboard::KeyCode;
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(keys: &[KeyCode], serializer: S) -> Result<S::Ok, S::Error> {
+        // A key with no name can't be read back, so it is left out rather than failing the
+        // whole file
+        serializer.collect_seq(keys.iter().filter_map(|&key| saved_name(key)))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<KeyCode>, D::Error> {
+        Vec::<String>::deserialize(deserializer)?
+            .iter()
+            .map(|name| {
+                parse_key(name).ok_or_else(|| D::Error::custom(format!("unknown key '{}'", name)))
+            })
+            .collect()
+    }
+}
+
+// Every key and the name it is saved under
+const KEY_NAMES: &[(KeyCode, &str)] = &[
+    (KeyCode::Key1, "Key1"),
+    (KeyCode::Key2, "Key2"),
+    (KeyCode::Key3, "Key3"),
+    (KeyCode::Key4, "Key4"),
+    (KeyCode::Key5, "Key5"),
+    (KeyCode::Key6, "Key6"),
+    (KeyCode::Key7, "Key7"),
+    (KeyCode::Key8, "Key8"),
+    (KeyCode::Key9, "Key9"),
+    (KeyCode::Key0, "Key0"),
+    (KeyCode::A, "A"),
+    (KeyCode::B, "B"),
+    (KeyCode::C, "C"),
+    (KeyCode::D, "D"),
+    (KeyCode::E, "E"),
+    (KeyCode::F, "F"),
+    (KeyCode::G, "G"),
+    (KeyCode::H, "H"),
+    (KeyCode::I, "I"),
+    (KeyCode::J, "J"),
+    (KeyCode::K, "K"),
+    (KeyCode::L, "L"),
+    (KeyCode::M, "M"),
+    (KeyCode::N, "N"),
+    (KeyCode::O, "O"),
+    (KeyCode::P, "P"),
+    (KeyCode::Q, "Q"),
+    (KeyCode::R, "R"),
+    (KeyCode::S, "S"),
+    (KeyCode::T, "T"),
+    (KeyCode::U, "U"),
+    (KeyCode::V, "V"),
+    (KeyCode::W, "W"),
+    (KeyCode::X, "X"),
+    (KeyCode::Y, "Y"),
+    (KeyCode::Z, "Z"),
+    (KeyCode::Escape, "Escape"),
+    (KeyCode::F1, "F1"),
+    (KeyCode::F2, "F2"),
+    (KeyCode::F3, "F3"),
+    (KeyCode::F4, "F4"),
+    (KeyCode::F5, "F5"),
+    (KeyCode::F6, "F6"),
+    (KeyCode::F7, "F7"),
+    (KeyCode::F8, "F8"),
+    (KeyCode::F9, "F9"),
+    (KeyCode::F10, "F10"),
+    (KeyCode::F11, "F11"),
+    (KeyCode::F12, "F12"),
+    (KeyCode::F13, "F13"),
+    (KeyCode::F14, "F14"),
+    (KeyCode::F15, "F15"),
+    (KeyCode::F16, "F16"),
+    (KeyCode::F17, "F17"),
+    (KeyCode::F18, "F18"),
+    (KeyCode::F19, "F19"),
+    (KeyCode::F20, "F20"),
+    (KeyCode::F21, "F21"),
+    (KeyCode::F22, "F22"),
+    (KeyCode::F23, "F23"),
+    (KeyCode::F24, "F24"),
+    (KeyCode::Snapshot, "Snapshot"),
+    (KeyCode::Scroll, "Scroll"),
+    (KeyCode::Pause, "Pause"),
+    (KeyCode::Insert, "Insert"),
+    (KeyCode::Home, "Home"),
+    (KeyCode::Delete, "Delete"),
+    (KeyCode::End, "End"),
+    (KeyCode::PageDown, "PageDown"),
+    (KeyCode::PageUp, "PageUp"),
+    (KeyCode::Left, "Left"),
+    (KeyCode::Up, "Up"),
+    (KeyCode::Right, "Right"),
+    (KeyCode::Down, "Down"),
+    (KeyCode::Back, "Back"),
+    (KeyCode::Return, "Return"),
+    (KeyCode::Space, "Space"),
+    (KeyCode::Compose, "Compose"),
+    (KeyCode::Caret, "Caret"),
+    (KeyCode::Numlock, "Numlock"),
+    (KeyCode::Numpad0, "Numpad0"),
+    (KeyCode::Numpad1, "Numpad1"),
+    (KeyCode::Numpad2, "Numpad2"),
+    (KeyCode::Numpad3, "Numpad3"),
+    (KeyCode::Numpad4, "Numpad4"),
+    (KeyCode::Numpad5, "Numpad5"),
+    (KeyCode::Numpad6, "Numpad6"),
+    (KeyCode::Numpad7, "Numpad7"),
+    (KeyCode::Numpad8, "Numpad8"),
+    (KeyCode::Numpad9, "Numpad9"),
+    (KeyCode::NumpadAdd, "NumpadAdd"),
+    (KeyCode::NumpadDivide, "NumpadDivide"),
+    (KeyCode::NumpadDecimal, "NumpadDecimal"),
+    (KeyCode::NumpadComma, "NumpadComma"),
+    (KeyCode::NumpadEnter, "NumpadEnter"),
+    (KeyCode::NumpadEquals, "NumpadEquals"),
+    (KeyCode::NumpadMultiply, "NumpadMultiply"),
+    (KeyCode::NumpadSubtract, "NumpadSubtract"),
+    (KeyCode::AbntC1, "AbntC1"),
+    (KeyCode::AbntC2, "AbntC2"),
+    (KeyCode::Apostrophe, "Apostrophe"),
+    (KeyCode::Apps, "Apps"),
+    (KeyCode::Asterisk, "Asterisk"),
+    (KeyCode::At, "At"),
+    (KeyCode::Ax, "Ax"),
+    (KeyCode::Backslash, "Backslash"),
+    (KeyCode::Calculator, "Calculator"),
+    (KeyCode::Capital, "Capital"),
+    (KeyCode::Colon, "Colon"),
+    (KeyCode::Comma, "Comma"),
+    (KeyCode::Convert, "Convert"),
+    (KeyCode::Equals, "Equals"),
+    (KeyCode::Grave, "Grave"),
+    (KeyCode::Kana, "Kana"),
+    (KeyCode::Kanji, "Kanji"),
+    (KeyCode::LAlt, "LAlt"),
+    (KeyCode::LBracket, "LBracket"),
+    (KeyCode::LControl, "LControl"),
+    (KeyCode::LShift, "LShift"),
+    (KeyCode::LWin, "LWin"),
+    (KeyCode::Mail, "Mail"),
+    (KeyCode::MediaSelect, "MediaSelect"),
+    (KeyCode::MediaStop, "MediaStop"),
+    (KeyCode::Minus, "Minus"),
+    (KeyCode::Mute, "Mute"),
+    (KeyCode::MyComputer, "MyComputer"),
+    (KeyCode::NavigateForward, "NavigateForward"),
+    (KeyCode::NavigateBackward, "NavigateBackward"),
+    (KeyCode::NextTrack, "NextTrack"),
+    (KeyCode::NoConvert, "NoConvert"),
+    (KeyCode::OEM102, "OEM102"),
+    (KeyCode::Period, "Period"),
+    (KeyCode::PlayPause, "PlayPause"),
+    (KeyCode::Plus, "Plus"),
+    (KeyCode::Power, "Power"),
+    (KeyCode::PrevTrack, "PrevTrack"),
+    (KeyCode::RAlt, "RAlt"),
+    (KeyCode::RBracket, "RBracket"),
+    (KeyCode::RControl, "RControl"),
+    (KeyCode::RShift, "RShift"),
+    (KeyCode::RWin, "RWin"),
+    (KeyCode::Semicolon, "Semicolon"),
+    (KeyCode::Slash, "Slash"),
+    (KeyCode::Sleep, "Sleep"),
+    (KeyCode::Stop, "Stop"),
+    (KeyCode::Sysrq, "Sysrq"),
+    (KeyCode::Tab, "Tab"),
+    (KeyCode::Underline, "Underline"),
+    (KeyCode::Unlabeled, "Unlabeled"),
+    (KeyCode::VolumeDown, "VolumeDown"),
+    (KeyCode::VolumeUp, "VolumeUp"),
+    (KeyCode::Wake, "Wake"),
+    (KeyCode::WebBack, "WebBack"),
+    (KeyCode::WebFavorites, "WebFavorites"),
+    (KeyCode::WebForward, "WebForward"),
+    (KeyCode::WebHome, "WebHome"),
+    (KeyCode::WebRefresh, "WebRefresh"),
+    (KeyCode::WebSearch, "WebSearch"),
+    (KeyCode::WebStop, "WebStop"),
+    (KeyCode::Yen, "Yen"),
+    (KeyCode::Copy, "Copy"),
+    (KeyCode::Paste, "Paste"),
+    (KeyCode::Cut, "Cut"),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn saved_bindings_load_back_the_same() {
+        let mut bindings = Bindings::default();
+        bindings.bind(Action::LeftUp, KeyCode::Key1);
+        bindings.bind(Action::Pause, KeyCode::P);
+        bindings.clear(Action::Fullscreen);
+
+        let path = std::env::temp_dir().join(format!("pong-controls-{}.toml", std::process::id()));
+        bindings.save(&path).unwrap();
+        let loaded = Bindings::load(&path);
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(loaded.unwrap(), bindings);
+    }
+
+    #[test]
+    fn binding_a_key_takes_it_from_other_actions() {
+        let mut bindings = Bindings::default();
+
+        // W moves the left paddle up by default
+        assert!(bindings.bind(Action::Pause, KeyCode::W));
+
+        assert!(bindings.is_bound(Action::Pause, KeyCode::W));
+        assert!(!bindings.is_bound(Action::LeftUp, KeyCode::W));
+        assert!(bindings.keys(Action::LeftUp).is_empty());
+    }
+
+    #[test]
+    fn every_named_key_round_trips_through_its_name() {
+        for &(key, name) in KEY_NAMES {
+            assert_eq!(key_name(key), name);
+            assert_eq!(parse_key(name), Some(key));
+        }
+    }
+
+    #[test]
+    fn keys_without_a_name_never_reach_the_file() {
+        // Whatever is bound, the file never has the placeholder shown for unnamed keys,
+        // which loading would reject
+        let mut bindings = Bindings::default();
+        for &(key, _) in KEY_NAMES {
+            assert!(bindings.bind(Action::CycleMode, key));
+        }
+        assert!(!bindings.to_toml().contains("\"?\""));
+        assert_eq!(
+            toml::from_str::<Bindings>(&bindings.to_toml()).unwrap(),
+            bindings
+        );
+
+        let error = toml::from_str::<Bindings>("pause = [\"?\"]").unwrap_err();
+        assert!(error.to_string().contains("unknown key '?'"));
+    }
+}
@@ -0,0 +1,285 @@
+use pongpong::ai::Difficulty;
+use pongpong::entities::side::Side;
+use pongpong::match_config::{BallValues, MatchConfig, MultiballScoring, ServeRule};
+use pongpong::rules::{MatchRules, SetRules};
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
+
+// Games needed to win a set when `--sets` is given without `--games-per-set`
+const DEFAULT_GAMES_PER_SET: u32 = 3;
+
+// Points between serve changes when `--serve alternate` is given without `--serve-every`
+const DEFAULT_SERVE_EVERY: u32 = 2;
+
+/// Shown for `--help`
+pub const USAGE: &str = "\
+Usage: pongpong [OPTIONS]
+
+Setup:
+  --config <PATH>          Read settings from PATH instead of pongpong.toml
+  --print-config           Print the settings in use as TOML and exit
+  --power-ups <PATH>       Read power-up definitions from PATH instead of powerups.toml
+  --print-power-ups        Print the power-up definitions in use as TOML and exit
+  --tick-rate <N>          Simulation steps per second
+  --seed <N>               Replay the match played with this seed
+  --window-size <WxH>      Window size in pixels, e.g. 1280x720
+  --fullscreen             Fill the screen
+  --ai <left|right|both>   Let the computer play that side
+  --mouse <left|right>     Play that side with the mouse or touchpad
+  --difficulty <easy|medium|hard>
+                           How well the computer plays (medium by default)
+  --headless               Play a computer-vs-computer match without a window and print the result
+
+Match:
+  --mode <classic|accelerating|rally-fever>
+  --points <N>             Points needed to win a game (also --score-limit)
+  --win-by-two             A game needs a two-point lead to win
+  --sets <N>               Play best of N sets
+  --games-per-set <N>      Games needed to win a set (3 by default)
+  --time-limit <SECONDS>   End the match when the clock runs out
+  --serve <random|loser|winner|alternate>
+  --serve-every <N>        Points between serve changes with --serve alternate (2 by default)
+  --no-ball-collisions     Multiball balls pass through each other
+  --survival-multiball     Every ball that goes out scores
+  --escalating-ball-points Each extra ball is worth one more point
+";
+
+/// Everything that can be set from the command line
+#[derive(Debug, Clone, PartialEq)]
+pub struct CliOptions {
+    pub config_path: Option<PathBuf>,
+    pub print_config: bool,
+    pub power_ups_path: Option<PathBuf>,
+    pub print_power_ups: bool,
+    pub tick_rate: Option<u32>,
+    pub seed: Option<u64>,
+    pub window_size: Option<(f32, f32)>,
+    pub fullscreen: bool,
+    pub computer_sides: Vec<Side>,
+    pub mouse_side: Option<Side>,
+    pub difficulty: Difficulty,
+    pub headless: bool,
+    pub help: bool,
+    pub match_config: MatchConfig,
+}
+
+impl CliOptions {
+    /// Parses the arguments that follow the program name
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut options = CliOptions {
+            config_path: None,
+            print_config: false,
+            power_ups_path: None,
+            print_power_ups: false,
+            tick_rate: None,
+            seed: None,
+            window_size: None,
+            fullscreen: false,
+            computer_sides: Vec::new(),
+            mouse_side: None,
+            difficulty: Difficulty::default(),
+            headless: false,
+            help: false,
+            match_config: MatchConfig::default(),
+        };
+        let mut rules = MatchRules::default();
+        let mut sets = None;
+        let mut games_per_set = DEFAULT_GAMES_PER_SET;
+        let mut serve = None;
+        let mut serve_every = DEFAULT_SERVE_EVERY;
+
+        let mut args = args.into_iter();
+        while let Some(flag) = args.next() {
+            let mut value = || args.next().ok_or_else(|| format!("{} needs a value", flag));
+            match flag.as_str() {
+                "--config" => options.config_path = Some(PathBuf::from(value()?)),
+                "--print-config" => options.print_config = true,
+                "--power-ups" => options.power_ups_path = Some(PathBuf::from(value()?)),
+                "--print-power-ups" => options.print_power_ups = true,
+                "--tick-rate" => options.tick_rate = Some(parse_value(&flag, &value()?)?),
+                "--seed" => options.seed = Some(parse_value(&flag, &value()?)?),
+                "--window-size" => options.window_size = Some(parse_window_size(&value()?)?),
+                "--fullscreen" => options.fullscreen = true,
+                "--ai" => {
+                    options.computer_sides = match value()?.as_str() {
+                        "left" => vec![Side::Left],
+                        "right" => vec![Side::Right],
+                        "both" => vec![Side::Left, Side::Right],
+                        other => {
+                            return Err(format!(
+                                "--ai expects left, right or both, got '{}'",
+                                other
+                            ))
+                        }
+                    }
+                }
+                "--mouse" => {
+                    options.mouse_side = match value()?.as_str() {
+                        "left" => Some(Side::Left),
+                        "right" => Some(Side::Right),
+                        other => {
+                            return Err(format!("--mouse expects left or right, got '{}'", other))
+                        }
+                    }
+                }
+                "--difficulty" => options.difficulty = parse_value(&flag, &value()?)?,
+                "--headless" => options.headless = true,
+                "--help" | "-h" => options.help = true,
+                "--mode" => options.match_config.game_mode = parse_value(&flag, &value()?)?,
+                "--points" | "--score-limit" => {
+                    rules.points_to_win = Some(parse_value(&flag, &value()?)?)
+                }
+                "--win-by-two" => rules.win_by_two = true,
+                "--sets" => sets = Some(parse_value(&flag, &value()?)?),
+                "--games-per-set" => games_per_set = parse_value(&flag, &value()?)?,
+                "--time-limit" => {
+                    let seconds = parse_value(&flag, &value()?)?;
+                    rules.time_limit = Some(Duration::from_secs(seconds));
+                }
+                "--serve" => serve = Some(value()?),
+                "--serve-every" => serve_every = parse_value(&flag, &value()?)?,
+                "--no-ball-collisions" => options.match_config.ball_collisions = false,
+                "--survival-multiball" => {
+                    options.match_config.multiball_scoring = MultiballScoring::Survival
+                }
+                "--escalating-ball-points" => {
+                    options.match_config.ball_values = BallValues::Escalating
+                }
+                other => return Err(format!("unknown option '{}', see --help", other)),
+            }
+        }
+
+        // A headless match is always computer against computer
+        if options.headless && !options.computer_sides.is_empty() {
+            return Err(
+                "--headless always lets the computer play both sides, drop --ai".to_string(),
+            );
+        }
+        if options.headless && options.mouse_side.is_some() {
+            return Err("--headless has no window to use the mouse in, drop --mouse".to_string());
+        }
+
+        if let Some(side) = options.mouse_side {
+            if options.computer_sides.contains(&side) {
+                return Err("--mouse and --ai can't play the same side".to_string());
+            }
+        }
+
+        // Match rules: games to `--points`, optionally grouped into `--sets`, against the clock
+        rules.sets = sets.map(|best_of| SetRules {
+            best_of,
+            games_per_set,
+        });
+        if rules.sets.is_some() && rules.points_to_win.is_none() {
+            return Err("--sets needs --points to say how many points win a game".to_string());
+        }
+        options.match_config.rules = rules;
+
+        options.match_config.serve_rule = match serve.as_deref() {
+            None | Some("random") => ServeRule::Random,
+            Some("loser") => ServeRule::LoserServes,
+            Some("winner") => ServeRule::WinnerServes,
+            Some("alternate") => ServeRule::Alternate { every: serve_every },
+            Some(other) => {
+                return Err(format!(
+                    "--serve expects random, loser, winner or alternate, got '{}'",
+                    other
+                ))
+            }
+        };
+
+        Ok(options)
+    }
+}
+
+fn parse_value<T: FromStr>(flag: &str, value: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("{} got an invalid value '{}'", flag, value))
+}
+
+// Reads a `<width>x<height>` window size
+fn parse_window_size(value: &str) -> Result<(f32, f32), String> {
+    let invalid = || {
+        format!(
+            "--window-size expects <width>x<height>, e.g. 1280x720, got '{}'",
+            value
+        )
+    };
+    let (width, height) = value.split_once('x').ok_or_else(invalid)?;
+    let width: f32 = width.parse().map_err(|_| invalid())?;
+    let height: f32 = height.parse().map_err(|_| invalid())?;
+    if !(width >= 1.0 && height >= 1.0) {
+        return Err(invalid());
+    }
+    Ok((width, height))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<CliOptions, String> {
+        CliOptions::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn no_arguments_give_the_defaults() {
+        let options = parse(&[]).unwrap();
+        assert_eq!(options.tick_rate, None);
+        assert!(options.computer_sides.is_empty());
+        assert_eq!(options.match_config, MatchConfig::default());
+    }
+
+    #[test]
+    fn rejects_unknown_flags() {
+        assert_eq!(
+            parse(&["--points", "5", "--speed"]),
+            Err("unknown option '--speed', see --help".to_string())
+        );
+    }
+
+    #[test]
+    fn rejects_a_flag_missing_its_value() {
+        assert_eq!(
+            parse(&["--tick-rate"]),
+            Err("--tick-rate needs a value".to_string())
+        );
+        assert_eq!(parse(&["--ai"]), Err("--ai needs a value".to_string()));
+    }
+
+    #[test]
+    fn parses_the_tick_rate() {
+        assert_eq!(parse(&["--tick-rate", "240"]).unwrap().tick_rate, Some(240));
+        for bad in ["fast", "-60", "1.5"] {
+            assert_eq!(
+                parse(&["--tick-rate", bad]),
+                Err(format!("--tick-rate got an invalid value '{}'", bad))
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_choosing_sides_in_a_headless_match() {
+        assert!(parse(&["--headless", "--ai", "left"]).is_err());
+        assert!(parse(&["--mouse", "right", "--headless"]).is_err());
+        assert!(parse(&["--headless", "--difficulty", "hard"]).is_ok());
+    }
+
+    #[test]
+    fn builds_the_match_rules() {
+        let options = parse(&["--points", "11", "--win-by-two", "--sets", "3"]).unwrap();
+        let rules = options.match_config.rules;
+        assert_eq!(rules.points_to_win, Some(11));
+        assert!(rules.win_by_two);
+        assert_eq!(
+            rules.sets,
+            Some(SetRules {
+                best_of: 3,
+                games_per_set: DEFAULT_GAMES_PER_SET,
+            })
+        );
+        assert!(parse(&["--sets", "3"]).is_err());
+    }
+}
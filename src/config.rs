@@ -0,0 +1,583 @@
+use crate::constants::*;
+use ggez::graphics::Color;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Config file read at startup when no `--config` path is given
+pub const DEFAULT_CONFIG_PATH: &str = "pongpong.toml";
+
+/// Every tuning value in the game, loaded from a TOML file.
+///
+/// Missing keys fall back to the values in `constants.rs`, so a config file only needs to
+/// list what it changes.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct GameConfig {
+    pub court: CourtConfig,
+    pub paddle: PaddleConfig,
+    pub ball: BallConfig,
+    pub simulation: SimulationConfig,
+    pub modes: ModesConfig,
+    pub power_ups: PowerUpConfig,
+    pub effects: EffectsConfig,
+    pub display: DisplayConfig,
+}
+
+/// Size of the playing field
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CourtConfig {
+    pub width: f32,
+    pub height: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PaddleConfig {
+    pub width: f32,
+    pub height: f32,
+    pub speed: f32,   // Pixels per second
+    pub padding: f32, // Gap between the paddle and its goal line
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BallConfig {
+    pub radius: f32,
+    pub speed: f32,     // Pixels per second
+    pub max_speed: f32, // Cap for power-ups and game modes
+    #[serde(with = "hex_color")]
+    pub color: Color,
+    pub max_serve_angle_degrees: f32,
+    pub max_serve_hold_time: f32, // Seconds before a held serve goes on its own
+    pub motion_blur_enabled: bool,
+    pub motion_blur_trail_count: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SimulationConfig {
+    pub tick_rate: u32,      // Fixed simulation steps per second
+    pub max_delta_time: f32, // Longest single step, in seconds
+    pub max_frame_time: f32, // Longest frame the simulation will catch up on, in seconds
+    pub max_ball_contacts_per_step: usize,
+}
+
+/// Settings for the game mode variants
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ModesConfig {
+    pub ball_acceleration: f32, // Speed gained per second in Accelerating mode
+    pub long_rally_threshold: i32,
+    pub long_rally_speed_multiplier: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PowerUpConfig {
+    pub enabled: bool,
+    pub size: f32,
+    pub spawn_interval: f32, // Seconds between spawns
+    pub duration: f32,       // Seconds an effect lasts
+    pub flash_threshold: f32,
+    pub flash_speed: f32,
+    pub shield_thickness: f32,
+    pub catch_hits: u32, // Balls a catch power-up holds
+}
+
+/// Particles and screen shake
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct EffectsConfig {
+    pub particles_enabled: bool,
+    pub max_particles: usize,
+    pub paddle_hit_particles: usize,
+    pub wall_hit_particles: usize,
+    pub ball_hit_particles: usize,
+    pub shield_break_particles: usize,
+    pub screen_shake_enabled: bool,
+    pub screen_shake_intensity: f32,
+    pub screen_shake_duration: f32,
+}
+
+/// Text, court markings and the countdown
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DisplayConfig {
+    pub text_padding: f32,
+    #[serde(with = "hex_color")]
+    pub center_line_color: Color,
+    pub center_line_width: f32,
+    pub center_line_dash_length: f32,
+    pub center_line_gap_length: f32,
+    pub countdown_seconds: i32,
+}
+
+impl Default for CourtConfig {
+    fn default() -> Self {
+        Self {
+            width: SCREEN_SIZE.0,
+            height: SCREEN_SIZE.1,
+        }
+    }
+}
+
+impl Default for PaddleConfig {
+    fn default() -> Self {
+        Self {
+            width: PLAYER_SIZE.0,
+            height: PLAYER_SIZE.1,
+            speed: PLAYER_SPEED,
+            padding: PLAYER_PADDING,
+        }
+    }
+}
+
+impl Default for BallConfig {
+    fn default() -> Self {
+        Self {
+            radius: BALL_RADIUS,
+            speed: BALL_SPEED,
+            max_speed: MAX_BALL_SPEED,
+            color: BALL_COLOR,
+            max_serve_angle_degrees: MAX_SERVE_ANGLE_DEGREES,
+            max_serve_hold_time: MAX_SERVE_HOLD_TIME,
+            motion_blur_enabled: MOTION_BLUR_ENABLED,
+            motion_blur_trail_count: MOTION_BLUR_TRAIL_COUNT,
+        }
+    }
+}
+
+impl Default for SimulationConfig {
+    fn default() -> Self {
+        Self {
+            tick_rate: SIMULATION_TICK_RATE,
+            max_delta_time: MAX_DELTA_TIME,
+            max_frame_time: MAX_FRAME_TIME,
+            max_ball_contacts_per_step: MAX_BALL_CONTACTS_PER_STEP,
+        }
+    }
+}
+
+impl Default for ModesConfig {
+    fn default() -> Self {
+        Self {
+            ball_acceleration: BALL_ACCELERATION_FACTOR,
+            long_rally_threshold: LONG_RALLY_THRESHOLD,
+            long_rally_speed_multiplier: LONG_RALLY_SPEED_MULTIPLIER,
+        }
+    }
+}
+
+impl Default for PowerUpConfig {
+    fn default() -> Self {
+        Self {
+            enabled: POWERUPS_ENABLED,
+            size: POWERUP_SIZE,
+            spawn_interval: POWERUP_SPAWN_INTERVAL,
+            duration: POWERUP_DURATION,
+            flash_threshold: POWERUP_FLASH_THRESHOLD,
+            flash_speed: POWERUP_FLASH_SPEED,
+            shield_thickness: POWERUP_SHIELD_THICKNESS,
+            catch_hits: POWERUP_CATCH_HITS,
+        }
+    }
+}
+
+impl Default for EffectsConfig {
+    fn default() -> Self {
+        Self {
+            particles_enabled: PARTICLES_ENABLED,
+            max_particles: MAX_PARTICLES,
+            paddle_hit_particles: PADDLE_HIT_PARTICLE_COUNT,
+            wall_hit_particles: WALL_HIT_PARTICLE_COUNT,
+            ball_hit_particles: BALL_HIT_PARTICLE_COUNT,
+            shield_break_particles: SHIELD_BREAK_PARTICLE_COUNT,
+            screen_shake_enabled: SCREEN_SHAKE_ENABLED,
+            screen_shake_intensity: SCREEN_SHAKE_INTENSITY,
+            screen_shake_duration: SCREEN_SHAKE_DURATION,
+        }
+    }
+}
+
+impl Default for DisplayConfig {
+    fn default() -> Self {
+        Self {
+            text_padding: TEXT_PADDING,
+            center_line_color: CENTER_LINE_COLOR,
+            center_line_width: CENTER_LINE_WIDTH,
+            center_line_dash_length: CENTER_LINE_DASH_LENGTH,
+            center_line_gap_length: CENTER_LINE_GAP_LENGTH,
+            countdown_seconds: COUNTDOWN_SECONDS,
+        }
+    }
+}
+
+/// Why a config file could not be used
+#[derive(Debug)]
+pub enum ConfigError {
+    /// The file could not be read
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    /// The file could not be saved
+    Write {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    /// The text is not valid TOML, has an unknown key or a value of the wrong type
+    Parse {
+        path: Option<PathBuf>,
+        message: String,
+    },
+
+    /// A value parsed fine but makes no sense for the game
+    Invalid {
+        field: &'static str,
+        message: String,
+    },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Io { path, source } => {
+                write!(f, "could not read {}: {}", path.display(), source)
+            }
+            ConfigError::Write { path, source } => {
+                write!(f, "could not write {}: {}", path.display(), source)
+            }
+            ConfigError::Parse {
+                path: Some(path),
+                message,
+            } => write!(
+                f,
+                "could not parse {}: {}",
+                path.display(),
+                message.trim_end()
+            ),
+            ConfigError::Parse {
+                path: None,
+                message,
+            } => write!(f, "could not parse config: {}", message.trim_end()),
+            ConfigError::Invalid { field, message } => {
+                write!(f, "invalid value for `{}`: {}", field, message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ConfigError::Io { source, .. } | ConfigError::Write { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+impl GameConfig {
+    /// Reads and validates a config file
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ConfigError> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path).map_err(|source| ConfigError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+
+        Self::parse(&text).map_err(|error| match error {
+            ConfigError::Parse { message, .. } => ConfigError::Parse {
+                path: Some(path.to_path_buf()),
+                message,
+            },
+            other => other,
+        })
+    }
+
+    /// Parses and validates config text
+    pub fn parse(text: &str) -> Result<Self, ConfigError> {
+        let config: GameConfig = toml::from_str(text).map_err(|error| ConfigError::Parse {
+            path: None,
+            message: error.to_string(),
+        })?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// The config as TOML, e.g. to start a new config file from
+    pub fn to_toml(&self) -> String {
+        let mut value = toml::Value::try_from(self).unwrap_or(toml::Value::Boolean(false));
+        tidy_floats(&mut value);
+        toml::to_string_pretty(&value).unwrap_or_default()
+    }
+
+    /// Checks that every value is usable
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        positive("court.width", self.court.width)?;
+        positive("court.height", self.court.height)?;
+
+        positive("paddle.width", self.paddle.width)?;
+        positive("paddle.height", self.paddle.height)?;
+        at_most("paddle.height", self.paddle.height, self.court.height)?;
+        non_negative("paddle.speed", self.paddle.speed)?;
+        non_negative("paddle.padding", self.paddle.padding)?;
+
+        positive("ball.radius", self.ball.radius)?;
+        positive("ball.speed", self.ball.speed)?;
+        at_least("ball.max_speed", self.ball.max_speed, self.ball.speed)?;
+        non_negative(
+            "ball.max_serve_angle_degrees",
+            self.ball.max_serve_angle_degrees,
+        )?;
+        below(
+            "ball.max_serve_angle_degrees",
+            self.ball.max_serve_angle_degrees,
+            90.0,
+        )?;
+        positive("ball.max_serve_hold_time", self.ball.max_serve_hold_time)?;
+
+        if self.simulation.tick_rate == 0 {
+            return Err(invalid("simulation.tick_rate", "must be at least 1"));
+        }
+        positive("simulation.max_delta_time", self.simulation.max_delta_time)?;
+        // A longer tick would be cut short and the game would run slow
+        if 1.0 / self.simulation.tick_rate as f32 > self.simulation.max_delta_time {
+            return Err(invalid(
+                "simulation.tick_rate",
+                &format!(
+                    "must be at least {} so a tick fits in simulation.max_delta_time",
+                    (1.0 / self.simulation.max_delta_time).ceil()
+                ),
+            ));
+        }
+        positive("simulation.max_frame_time", self.simulation.max_frame_time)?;
+        if self.simulation.max_ball_contacts_per_step == 0 {
+            return Err(invalid(
+                "simulation.max_ball_contacts_per_step",
+                "must be at least 1",
+            ));
+        }
+
+        non_negative("modes.ball_acceleration", self.modes.ball_acceleration)?;
+        if self.modes.long_rally_threshold < 0 {
+            return Err(invalid(
+                "modes.long_rally_threshold",
+                &format!(
+                    "must not be negative, got {}",
+                    self.modes.long_rally_threshold
+                ),
+            ));
+        }
+        positive(
+            "modes.long_rally_speed_multiplier",
+            self.modes.long_rally_speed_multiplier,
+        )?;
+
+        // Power-ups spawn at least two sizes away from the edges and the center line
+        positive("power_ups.size", self.power_ups.size)?;
+        below(
+            "power_ups.size",
+            self.power_ups.size,
+            self.court.width.min(self.court.height * 2.0) / 8.0,
+        )?;
+        positive("power_ups.spawn_interval", self.power_ups.spawn_interval)?;
+        positive("power_ups.duration", self.power_ups.duration)?;
+        non_negative("power_ups.flash_threshold", self.power_ups.flash_threshold)?;
+        non_negative("power_ups.flash_speed", self.power_ups.flash_speed)?;
+        positive(
+            "power_ups.shield_thickness",
+            self.power_ups.shield_thickness,
+        )?;
+        below(
+            "power_ups.shield_thickness",
+            self.power_ups.shield_thickness,
+            self.court.width / 2.0,
+        )?;
+        if self.power_ups.catch_hits == 0 {
+            return Err(invalid("power_ups.catch_hits", "must be at least 1"));
+        }
+
+        positive(
+            "effects.screen_shake_intensity",
+            self.effects.screen_shake_intensity,
+        )?;
+        positive(
+            "effects.screen_shake_duration",
+            self.effects.screen_shake_duration,
+        )?;
+
+        non_negative("display.text_padding", self.display.text_padding)?;
+        positive("display.center_line_width", self.display.center_line_width)?;
+        positive(
+            "display.center_line_dash_length",
+            self.display.center_line_dash_length,
+        )?;
+        non_negative(
+            "display.center_line_gap_length",
+            self.display.center_line_gap_length,
+        )?;
+        if self.display.countdown_seconds < 0 {
+            return Err(invalid(
+                "display.countdown_seconds",
+                &format!(
+                    "must not be negative, got {}",
+                    self.display.countdown_seconds
+                ),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+/// Settings given on the command line. They win over the config file, also when it is
+/// reloaded while the game runs.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ConfigOverrides {
+    pub tick_rate: Option<u32>,
+}
+
+impl ConfigOverrides {
+    /// Puts the overrides into `config` and checks it is still usable
+    pub fn apply(&self, config: &mut GameConfig) -> Result<(), ConfigError> {
+        if let Some(tick_rate) = self.tick_rate {
+            config.simulation.tick_rate = tick_rate;
+        }
+        config.validate()
+    }
+}
+
+// Writes f32 settings the way they appear in the source (0.05, not 0.05000000074505806)
+fn tidy_floats(value: &mut toml::Value) {
+    match value {
+        toml::Value::Float(float) => {
+            *float = (*float as f32).to_string().parse().unwrap_or(*float);
+        }
+        toml::Value::Table(table) => table.iter_mut().for_each(|(_, value)| tidy_floats(value)),
+        toml::Value::Array(array) => array.iter_mut().for_each(tidy_floats),
+        _ => {}
+    }
+}
+
+pub(crate) fn invalid(field: &'static str, message: &str) -> ConfigError {
+    ConfigError::Invalid {
+        field,
+        message: message.to_string(),
+    }
+}
+
+// The comparisons are written so that NaN fails them too
+pub(crate) fn positive(field: &'static str, value: f32) -> Result<(), ConfigError> {
+    if value > 0.0 && value.is_finite() {
+        Ok(())
+    } else {
+        Err(invalid(
+            field,
+            &format!("must be greater than 0, got {}", value),
+        ))
+    }
+}
+
+pub(crate) fn non_negative(field: &'static str, value: f32) -> Result<(), ConfigError> {
+    if value >= 0.0 && value.is_finite() {
+        Ok(())
+    } else {
+        Err(invalid(
+            field,
+            &format!("must not be negative, got {}", value),
+        ))
+    }
+}
+
+fn at_least(field: &'static str, value: f32, min: f32) -> Result<(), ConfigError> {
+    if value >= min && value.is_finite() {
+        Ok(())
+    } else {
+        Err(invalid(
+            field,
+            &format!("must be at least {}, got {}", min, value),
+        ))
+    }
+}
+
+fn at_most(field: &'static str, value: f32, max: f32) -> Result<(), ConfigError> {
+    if value <= max {
+        Ok(())
+    } else {
+        Err(invalid(
+            field,
+            &format!("must be at most {}, got {}", max, value),
+        ))
+    }
+}
+
+fn below(field: &'static str, value: f32, limit: f32) -> Result<(), ConfigError> {
+    if value < limit {
+        Ok(())
+    } else {
+        Err(invalid(
+            field,
+            &format!("must be less than {}, got {}", limit, value),
+        ))
+    }
+}
+
+/// Parses a `#rrggbb` or `#rrggbbaa` color
+pub fn parse_hex_color(text: &str) -> Option<Color> {
+    let digits = text.strip_prefix('#')?;
+    if !matches!(digits.len(), 6 | 8) || !digits.is_ascii() {
+        return None;
+    }
+
+    let channel = |index: usize| u8::from_str_radix(&digits[index..index + 2], 16).ok();
+    let alpha = if digits.len() == 8 { channel(6)? } else { 255 };
+
+    Some(Color::from_rgba(
+        channel(0)?,
+        channel(2)?,
+        channel(4)?,
+        alpha,
+    ))
+}
+
+// Colors are written as hex strings in config files
+pub(crate) mod hex_color {
+    use ggez::graphics::Color;
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(color: &Color, serializer: S) -> Result<S::Ok, S::Error> {
+        let (r, g, b, a) = color.to_rgba();
+        serializer.serialize_str(&format!("#{:02x}{:02x}{:02x}{:02x}", r, g, b, a))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Color, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        super::parse_hex_color(&text).ok_or_else(|| {
+            D::Error::custom(format!(
+                "expected a color like \"#3366ff\" or \"#3366ff80\", got \"{}\"",
+                text
+            ))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tick_rate_must_fit_a_tick_in_max_delta_time() {
+        let mut config = GameConfig::default();
+        config.simulation.max_delta_time = 0.05;
+
+        config.simulation.tick_rate = 10;
+        assert!(config.validate().is_err());
+
+        config.simulation.tick_rate = 20;
+        assert!(config.validate().is_ok());
+    }
+}
@@ -0,0 +1,90 @@
+use crate::config::{ConfigError, ConfigOverrides, GameConfig};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+// How often the config file's modification time is checked
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Watches a config file and reloads it whenever it is saved
+pub struct ConfigWatcher {
+    path: PathBuf,
+    overrides: ConfigOverrides,   // Put back into every reloaded config
+    modified: Option<SystemTime>, // Modification time of the version last loaded
+    since_check: Duration,
+}
+
+impl ConfigWatcher {
+    /// Starts watching `path`. The file doesn't need to exist yet. Reloaded configs keep
+    /// the `overrides` given on the command line.
+    pub fn new(path: impl Into<PathBuf>, overrides: ConfigOverrides) -> Self {
+        let path = path.into();
+        Self {
+            modified: modified_time(&path),
+            path,
+            overrides,
+            since_check: Duration::ZERO,
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the reloaded config, or why it couldn't be used, once the file has changed
+    pub fn poll(&mut self, delta: Duration) -> Option<Result<GameConfig, ConfigError>> {
+        self.since_check += delta;
+        if self.since_check < POLL_INTERVAL {
+            return None;
+        }
+        self.since_check = Duration::ZERO;
+
+        // A missing file keeps the current settings
+        let modified = modified_time(&self.path);
+        if modified.is_none() || modified == self.modified {
+            return None;
+        }
+        self.modified = modified;
+
+        Some(GameConfig::load(&self.path).and_then(|mut config| {
+            self.overrides.apply(&mut config)?;
+            Ok(config)
+        }))
+    }
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::File;
+
+    #[test]
+    fn reloaded_config_keeps_the_command_line_overrides() {
+        let path = std::env::temp_dir().join(format!("pong-watch-{}.toml", std::process::id()));
+        std::fs::write(&path, "[simulation]\ntick_rate = 60\n").unwrap();
+        let overrides = ConfigOverrides {
+            tick_rate: Some(240),
+        };
+        let mut watcher = ConfigWatcher::new(&path, overrides);
+
+        // Save the file again, with a different time so the change is noticed
+        std::fs::write(&path, "[ball]\nspeed = 500.0\n").unwrap();
+        File::options()
+            .write(true)
+            .open(&path)
+            .and_then(|file| file.set_modified(SystemTime::now() + Duration::from_secs(5)))
+            .unwrap();
+
+        let reloaded = watcher.poll(POLL_INTERVAL);
+        std::fs::remove_file(&path).ok();
+
+        let config = reloaded.expect("change should be noticed").unwrap();
+        assert_eq!(config.ball.speed, 500.0);
+        assert_eq!(config.simulation.tick_rate, 240);
+    }
+}
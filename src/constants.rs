@@ -1,3 +1,4 @@
+// Built-in defaults for `GameConfig`. Override them in a config file rather than here.
 use ggez::graphics::Color;
 
 // Screen and game settings
@@ -10,9 +11,13 @@ pub const PLAYER_PADDING: f32 = 5.0; // Player padding
 pub const BALL_RADIUS: f32 = 10.0; // Ball radius
 pub const BALL_SPEED: f32 = 400.0; // Increased ball speed for smoother animation
 pub const BALL_COLOR: Color = Color::new(0.0, 0.0, 1.0, 1.0); // Ball color
+pub const MAX_SERVE_ANGLE_DEGREES: f32 = 45.0; // Serve angle when served off a paddle end
+pub const MAX_SERVE_HOLD_TIME: f32 = 5.0; // A held serve is released on its own after this many seconds
 
 // Frame rate settings
 pub const MAX_DELTA_TIME: f32 = 1.0 / 20.0; // Cap delta time to prevent physics issues
+pub const SIMULATION_TICK_RATE: u32 = 120; // Fixed simulation steps per second
+pub const MAX_FRAME_TIME: f32 = 0.25; // Longest frame the simulation will try to catch up on
 pub const MOTION_BLUR_ENABLED: bool = true; // Enable motion blur for smoother ball movement
 pub const MOTION_BLUR_TRAIL_COUNT: usize = 3; // Number of motion blur trails
 
@@ -30,6 +35,8 @@ pub const PARTICLES_ENABLED: bool = true; // Enable particle effects
 pub const MAX_PARTICLES: usize = 200; // Maximum number of particles
 pub const PADDLE_HIT_PARTICLE_COUNT: usize = 15; // Particles to emit on paddle hit
 pub const WALL_HIT_PARTICLE_COUNT: usize = 5; // Particles to emit on wall hit
+pub const BALL_HIT_PARTICLE_COUNT: usize = 10; // Particles to emit when two balls collide
+pub const SHIELD_BREAK_PARTICLE_COUNT: usize = 40; // Particles to emit when a shield breaks
 
 // Screen shake settings
 pub const SCREEN_SHAKE_ENABLED: bool = true; // Enable screen shake effect
@@ -46,9 +53,12 @@ pub const POWERUP_SPAWN_INTERVAL: f32 = 10.0; // Time between power-up spawns in
 pub const POWERUP_DURATION: f32 = 5.0; // Duration of power-up effects in seconds
 pub const POWERUP_FLASH_THRESHOLD: f32 = 1.0; // When a power-up starts flashing (seconds remaining)
 pub const POWERUP_FLASH_SPEED: f32 = 8.0; // How fast the power-up flashes (cycles per second)
+pub const POWERUP_SHIELD_THICKNESS: f32 = 4.0; // Width of the shield bar on the goal line
+pub const POWERUP_CATCH_HITS: u32 = 3; // Balls a catch power-up holds before it wears off
 
 // Game variant settings
 pub const BALL_ACCELERATION_FACTOR: f32 = 10.0; // How much the ball accelerates per second
 pub const MAX_BALL_SPEED: f32 = 800.0; // Maximum ball speed for acceleration mode
+pub const MAX_BALL_CONTACTS_PER_STEP: usize = 4; // Bounces resolved per ball in one simulation step
 pub const LONG_RALLY_THRESHOLD: i32 = 5; // Number of hits to consider a rally "long"
 pub const LONG_RALLY_SPEED_MULTIPLIER: f32 = 1.2; // Speed multiplier for long rallies
@@ -0,0 +1,52 @@
+use super::gamepad::GamepadInput;
+use crate::bindings::Bindings;
+use crate::entities::side::Side;
+use crate::simulation::{PaddleInput, Simulation};
+use ggez::glam::Vec2;
+use ggez::input::keyboard::KeyCode;
+use ggez::input::mouse::MouseButton;
+
+/// Input from the window, passed on to every controller. Gamepad input only goes to the
+/// side the pad is assigned to.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum InputEvent {
+    KeyDown(KeyCode),
+    KeyUp(KeyCode),
+    FocusLost, // Keys let go while the window is in the background are never reported
+    MouseMoved(Vec2), // The pointer's new position, in court units
+    MouseDown(MouseButton),
+    Gamepad(GamepadInput),
+}
+
+/// Something that moves a paddle: a person on a keyboard, the computer, a script, and so on.
+/// Each side of the court has one, and it can be swapped at any time.
+pub trait Controller {
+    // Produces the command for the paddle on `side` for the next simulation step
+    fn command(&mut self, simulation: &Simulation, side: Side, delta_time: f32) -> PaddleInput;
+
+    // Drops serve presses made while the simulation wasn't stepping, e.g. during a pause,
+    // so they don't fire on the first tick back. Nothing else about the controller moves on.
+    fn discard_pending(&mut self) {}
+
+    // Reacts to window input. Controllers that don't read the window ignore it.
+    fn handle_event(&mut self, _event: &InputEvent) {}
+
+    // Picks up changed key bindings for the paddle on `side`
+    fn rebind(&mut self, _bindings: &Bindings, _side: Side) {}
+
+    // Whether the computer is playing
+    fn is_computer(&self) -> bool {
+        false
+    }
+
+    // Whether a newly plugged-in gamepad may take this side over. Only keyboard players
+    // hand their paddle to a pad; the computer keeps its side.
+    fn yields_to_gamepad(&self) -> bool {
+        !self.is_computer()
+    }
+
+    // Tells a person how to serve, e.g. "press D". None when nobody needs telling.
+    fn serve_prompt(&self) -> Option<String> {
+        None
+    }
+}
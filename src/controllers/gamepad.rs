@@ -0,0 +1,274 @@
+use super::controller::{Controller, InputEvent};
+use crate::entities::side::Side;
+use crate::simulation::{PaddleInput, Simulation};
+use ggez::input::gamepad::gilrs::{Axis, Button};
+
+// Stick deflection below this is treated as resting, since sticks rarely center exactly
+const STICK_DEAD_ZONE: f32 = 0.2;
+
+/// Gamepad input for one side's pad. The game builds these from ggez's gamepad events,
+/// and tests can make them up without any hardware.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GamepadInput {
+    ButtonDown(Button),
+    ButtonUp(Button),
+    Axis(Axis, f32),
+    Disconnected,
+}
+
+/// A paddle played on a gamepad. The left stick sets the paddle's speed in proportion to how far
+/// it is pushed, the D-pad moves it at full speed and the bottom face button serves.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GamepadController {
+    stick: f32, // Left stick's vertical deflection, -1 (down) to 1 (up) as gilrs reports it
+    dpad_up: bool,
+    dpad_down: bool,
+    release: bool, // Serve button pressed since the last command
+}
+
+impl GamepadController {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // The paddle movement the pad is asking for, from -1 (up) to 1 (down)
+    pub fn movement(&self) -> f32 {
+        if self.dpad_up != self.dpad_down {
+            return if self.dpad_up { -1.0 } else { 1.0 };
+        }
+
+        // Rescale past the dead zone so the paddle can still creep along slowly
+        let deflection = self.stick.abs();
+        if deflection <= STICK_DEAD_ZONE {
+            return 0.0;
+        }
+        let speed = ((deflection - STICK_DEAD_ZONE) / (1.0 - STICK_DEAD_ZONE)).min(1.0);
+        -self.stick.signum() * speed
+    }
+}
+
+impl Controller for GamepadController {
+    fn command(&mut self, _simulation: &Simulation, _side: Side, _delta_time: f32) -> PaddleInput {
+        PaddleInput {
+            movement: self.movement(),
+            target: None,
+            release: std::mem::take(&mut self.release),
+        }
+    }
+
+    fn discard_pending(&mut self) {
+        self.release = false;
+    }
+
+    fn handle_event(&mut self, event: &InputEvent) {
+        let InputEvent::Gamepad(input) = *event else {
+            return;
+        };
+
+        match input {
+            GamepadInput::ButtonDown(Button::South) => self.release = true,
+            GamepadInput::ButtonDown(Button::DPadUp) => self.dpad_up = true,
+            GamepadInput::ButtonDown(Button::DPadDown) => self.dpad_down = true,
+            GamepadInput::ButtonUp(Button::DPadUp) => self.dpad_up = false,
+            GamepadInput::ButtonUp(Button::DPadDown) => self.dpad_down = false,
+            GamepadInput::Axis(Axis::LeftStickY, value) => self.stick = value.clamp(-1.0, 1.0),
+            // An unplugged pad can't report letting go, so let go of everything now
+            GamepadInput::Disconnected => *self = Self::default(),
+            _ => {}
+        }
+    }
+
+    fn serve_prompt(&self) -> Option<String> {
+        Some("press A".to_string())
+    }
+}
+
+// A side's claim on a gamepad
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct PadSlot<Id> {
+    id: Id,
+    connected: bool,
+}
+
+/// Decides which side each gamepad plays. Pads take the first open side in the order they are
+/// seen. An unplugged pad's side is kept for it, and the next pad plugged in takes it back
+/// even if the system gives that pad a new id.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PadAssigner<Id> {
+    slots: [Option<PadSlot<Id>>; 2], // Left, then right
+}
+
+impl<Id> Default for PadAssigner<Id> {
+    fn default() -> Self {
+        Self {
+            slots: [None, None],
+        }
+    }
+}
+
+impl<Id: Copy + PartialEq> PadAssigner<Id> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Gives a connected pad a side, preferring an empty one over one whose pad was unplugged.
+    // Only `open_sides` can take a new pad. Returns the pad's side, if it has one.
+    pub fn connect(&mut self, id: Id, open_sides: &[Side]) -> Option<Side> {
+        if let Some(side) = self.side_of(id) {
+            if let Some(slot) = &mut self.slots[side.index()] {
+                slot.connected = true;
+            }
+            return Some(side);
+        }
+
+        let free = |connected_ok: bool| {
+            open_sides
+                .iter()
+                .copied()
+                .find(|&side| match self.slots[side.index()] {
+                    None => true,
+                    Some(slot) => connected_ok && !slot.connected,
+                })
+        };
+        let side = free(false).or_else(|| free(true))?;
+        self.slots[side.index()] = Some(PadSlot {
+            id,
+            connected: true,
+        });
+        Some(side)
+    }
+
+    // Marks a pad as unplugged and returns the side it was playing
+    pub fn disconnect(&mut self, id: Id) -> Option<Side> {
+        let side = self.side_of(id)?;
+        let slot = self.slots[side.index()].as_mut()?;
+        if !slot.connected {
+            return None;
+        }
+        slot.connected = false;
+        Some(side)
+    }
+
+    pub fn side_of(&self, id: Id) -> Option<Side> {
+        [Side::Left, Side::Right]
+            .into_iter()
+            .find(|&side| self.slots[side.index()].is_some_and(|slot| slot.id == id))
+    }
+
+    // Pads that are assigned and still plugged in
+    pub fn connected(&self) -> impl Iterator<Item = Id> + '_ {
+        self.slots
+            .iter()
+            .flatten()
+            .filter(|slot| slot.connected)
+            .map(|slot| slot.id)
+    }
+
+    // Whether the pad playing `side` has been unplugged and not yet replaced
+    pub fn is_waiting(&self, side: Side) -> bool {
+        self.slots[side.index()].is_some_and(|slot| !slot.connected)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BOTH_SIDES: [Side; 2] = [Side::Left, Side::Right];
+
+    #[test]
+    fn pads_take_the_first_open_side_in_turn() {
+        let mut pads = PadAssigner::new();
+
+        assert_eq!(pads.connect(7, &BOTH_SIDES), Some(Side::Left));
+        assert_eq!(pads.connect(3, &BOTH_SIDES), Some(Side::Right));
+        assert_eq!(pads.connect(9, &BOTH_SIDES), None);
+
+        // A pad seen again keeps its side
+        assert_eq!(pads.connect(7, &BOTH_SIDES), Some(Side::Left));
+    }
+
+    #[test]
+    fn pads_only_take_open_sides() {
+        let mut pads = PadAssigner::new();
+
+        assert_eq!(pads.connect(1, &[Side::Right]), Some(Side::Right));
+        assert_eq!(pads.connect(2, &[Side::Right]), None);
+    }
+
+    #[test]
+    fn disconnecting_leaves_the_side_waiting() {
+        let mut pads = PadAssigner::new();
+        pads.connect(1, &BOTH_SIDES);
+
+        assert_eq!(pads.disconnect(1), Some(Side::Left));
+        assert!(pads.is_waiting(Side::Left));
+        assert_eq!(pads.connected().count(), 0);
+
+        // Only the first unplug counts
+        assert_eq!(pads.disconnect(1), None);
+        assert_eq!(pads.disconnect(2), None);
+    }
+
+    #[test]
+    fn unplugged_pad_gets_its_side_back() {
+        let mut pads = PadAssigner::new();
+        pads.connect(1, &BOTH_SIDES);
+        pads.connect(2, &BOTH_SIDES);
+        pads.disconnect(1);
+
+        assert_eq!(pads.connect(1, &BOTH_SIDES), Some(Side::Left));
+        assert!(!pads.is_waiting(Side::Left));
+    }
+
+    #[test]
+    fn new_pad_reclaims_a_waiting_side_only_when_none_is_empty() {
+        let mut pads = PadAssigner::new();
+        pads.connect(1, &BOTH_SIDES);
+        pads.disconnect(1);
+
+        // The empty right side comes first
+        assert_eq!(pads.connect(5, &BOTH_SIDES), Some(Side::Right));
+
+        // Then the unplugged pad's side, even under a new id
+        assert_eq!(pads.connect(6, &BOTH_SIDES), Some(Side::Left));
+        assert!(!pads.is_waiting(Side::Left));
+        assert_eq!(pads.side_of(1), None);
+    }
+
+    #[test]
+    fn disconnect_lets_go_of_everything() {
+        let mut pad = GamepadController::new();
+        pad.handle_event(&InputEvent::Gamepad(GamepadInput::ButtonDown(
+            Button::DPadUp,
+        )));
+        pad.handle_event(&InputEvent::Gamepad(GamepadInput::ButtonDown(
+            Button::South,
+        )));
+        pad.handle_event(&InputEvent::Gamepad(GamepadInput::Axis(
+            Axis::LeftStickY,
+            0.9,
+        )));
+
+        pad.handle_event(&InputEvent::Gamepad(GamepadInput::Disconnected));
+
+        assert_eq!(pad, GamepadController::default());
+        assert_eq!(pad.movement(), 0.0);
+    }
+
+    #[test]
+    fn stick_inside_the_dead_zone_rests() {
+        let mut pad = GamepadController::new();
+        pad.handle_event(&InputEvent::Gamepad(GamepadInput::Axis(
+            Axis::LeftStickY,
+            STICK_DEAD_ZONE / 2.0,
+        )));
+        assert_eq!(pad.movement(), 0.0);
+
+        pad.handle_event(&InputEvent::Gamepad(GamepadInput::Axis(
+            Axis::LeftStickY,
+            1.0,
+        )));
+        assert_eq!(pad.movement(), -1.0);
+    }
+}
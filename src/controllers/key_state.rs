@@ -0,0 +1,97 @@
+use ggez::input::keyboard::KeyCode;
+
+/// The keys that are held down right now, oldest press first
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct KeyState {
+    held: Vec<KeyCode>,
+}
+
+impl KeyState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Key repeats of a key that is already held don't count as a new press
+    pub fn press(&mut self, key: KeyCode) {
+        if !self.held.contains(&key) {
+            self.held.push(key);
+        }
+    }
+
+    pub fn release(&mut self, key: KeyCode) {
+        self.held.retain(|&held| held != key);
+    }
+
+    // Forgets every key, e.g. when the window loses focus and releases would go unseen
+    pub fn clear(&mut self) {
+        self.held.clear();
+    }
+
+    pub fn is_held(&self, key: KeyCode) -> bool {
+        self.held.contains(&key)
+    }
+
+    // Of the given keys, the one pressed most recently that is still held. Holding one key,
+    // pressing another and letting go of it again goes back to the first.
+    pub fn last_pressed(&self, keys: &[KeyCode]) -> Option<KeyCode> {
+        self.held
+            .iter()
+            .rev()
+            .copied()
+            .find(|key| keys.contains(key))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DIRECTION_KEYS: [KeyCode; 2] = [KeyCode::W, KeyCode::S];
+
+    #[test]
+    fn letting_go_of_a_tapped_key_goes_back_to_the_held_one() {
+        let mut keys = KeyState::new();
+
+        keys.press(KeyCode::S);
+        assert_eq!(keys.last_pressed(&DIRECTION_KEYS), Some(KeyCode::S));
+
+        keys.press(KeyCode::W);
+        assert_eq!(keys.last_pressed(&DIRECTION_KEYS), Some(KeyCode::W));
+
+        keys.release(KeyCode::W);
+        assert_eq!(keys.last_pressed(&DIRECTION_KEYS), Some(KeyCode::S));
+    }
+
+    #[test]
+    fn key_repeat_does_not_count_as_a_new_press() {
+        let mut keys = KeyState::new();
+        keys.press(KeyCode::S);
+        keys.press(KeyCode::W);
+
+        // S repeating while held doesn't take over from W
+        keys.press(KeyCode::S);
+        assert_eq!(keys.last_pressed(&DIRECTION_KEYS), Some(KeyCode::W));
+    }
+
+    #[test]
+    fn other_keys_are_ignored() {
+        let mut keys = KeyState::new();
+        keys.press(KeyCode::S);
+        keys.press(KeyCode::D);
+
+        assert_eq!(keys.last_pressed(&DIRECTION_KEYS), Some(KeyCode::S));
+    }
+
+    #[test]
+    fn clear_lets_go_of_everything() {
+        let mut keys = KeyState::new();
+        keys.press(KeyCode::S);
+        keys.press(KeyCode::W);
+
+        keys.clear();
+
+        assert!(!keys.is_held(KeyCode::S));
+        assert!(!keys.is_held(KeyCode::W));
+        assert_eq!(keys.last_pressed(&DIRECTION_KEYS), None);
+    }
+}
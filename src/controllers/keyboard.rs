@@ -0,0 +1,112 @@
+use super::controller::{Controller, InputEvent};
+use super::key_state::KeyState;
+use crate::bindings::{key_name, Action, Bindings};
+use crate::entities::{direction::Direction, side::Side};
+use crate::simulation::{PaddleInput, Simulation};
+use ggez::input::keyboard::KeyCode;
+
+/// A paddle played on the keyboard. Each move can have several keys; when keys for both
+/// directions are held, the one pressed last wins.
+pub struct KeyboardController {
+    pub up: Vec<KeyCode>,
+    pub down: Vec<KeyCode>,
+    pub serve: Vec<KeyCode>,
+    keys: KeyState,
+    release: bool, // Serve key pressed since the last command
+}
+
+impl KeyboardController {
+    pub fn new(up: Vec<KeyCode>, down: Vec<KeyCode>, serve: Vec<KeyCode>) -> Self {
+        Self {
+            up,
+            down,
+            serve,
+            keys: KeyState::new(),
+            release: false,
+        }
+    }
+
+    // The keys bound to one side's paddle
+    pub fn from_bindings(bindings: &Bindings, side: Side) -> Self {
+        let mut controller = Self::new(Vec::new(), Vec::new(), Vec::new());
+        controller.rebind(bindings, side);
+        controller
+    }
+
+    // The usual keys for each side: W, S and D on the left, the arrow keys on the right
+    pub fn for_side(side: Side) -> Self {
+        Self::from_bindings(&Bindings::default(), side)
+    }
+
+    // The direction of whichever direction key is in charge
+    fn direction(&self) -> Option<Direction> {
+        let direction_keys: Vec<KeyCode> = self.up.iter().chain(&self.down).copied().collect();
+        match self.keys.last_pressed(&direction_keys) {
+            Some(key) if self.up.contains(&key) => Some(Direction::Up),
+            Some(_) => Some(Direction::Down),
+            None => None,
+        }
+    }
+}
+
+impl Controller for KeyboardController {
+    fn command(&mut self, _simulation: &Simulation, _side: Side, _delta_time: f32) -> PaddleInput {
+        PaddleInput {
+            movement: self.direction().map_or(0.0, Direction::axis),
+            target: None,
+            // A key press releases the serve once, not on every tick
+            release: std::mem::take(&mut self.release),
+        }
+    }
+
+    fn discard_pending(&mut self) {
+        self.release = false;
+    }
+
+    fn handle_event(&mut self, event: &InputEvent) {
+        match *event {
+            InputEvent::KeyDown(key) => {
+                if self.serve.contains(&key) && !self.keys.is_held(key) {
+                    self.release = true;
+                }
+                self.keys.press(key);
+            }
+            InputEvent::KeyUp(key) => self.keys.release(key),
+            InputEvent::FocusLost => self.keys.clear(),
+            _ => {}
+        }
+    }
+
+    fn rebind(&mut self, bindings: &Bindings, side: Side) {
+        let [up, down, serve] = Action::paddle_actions(side);
+        self.up = bindings.keys(up).to_vec();
+        self.down = bindings.keys(down).to_vec();
+        self.serve = bindings.keys(serve).to_vec();
+    }
+
+    fn serve_prompt(&self) -> Option<String> {
+        let key = self.serve.first()?;
+        Some(format!("press {}", key_name(*key)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::GameConfig;
+    use crate::match_config::MatchConfig;
+
+    #[test]
+    fn discarding_drops_the_serve_press_but_not_held_keys() {
+        let simulation = Simulation::new(GameConfig::default(), MatchConfig::default(), 1);
+        let mut keyboard = KeyboardController::for_side(Side::Left);
+        keyboard.handle_event(&InputEvent::KeyDown(KeyCode::D));
+        keyboard.handle_event(&InputEvent::KeyDown(KeyCode::S));
+
+        keyboard.discard_pending();
+        let input = keyboard.command(&simulation, Side::Left, 0.01);
+
+        assert!(!input.release);
+        assert_eq!(input.movement, 1.0);
+    }
+}
@@ -0,0 +1,6 @@
+pub mod controller;
+pub mod gamepad;
+pub mod key_state;
+pub mod keyboard;
+pub mod mouse;
+pub mod scripted;
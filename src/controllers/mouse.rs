@@ -0,0 +1,51 @@
+use super::controller::{Controller, InputEvent};
+use crate::entities::side::Side;
+use crate::simulation::{PaddleInput, Simulation};
+use ggez::input::mouse::MouseButton;
+
+/// A paddle that follows the mouse (or touchpad) up and down. The paddle heads for the pointer
+/// at up to its top speed, so power-ups that change its speed still count, and a left click
+/// serves.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MouseController {
+    pointer_y: Option<f32>, // Where the pointer was last seen, in court units
+    release: bool,          // Clicked since the last command
+}
+
+impl MouseController {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Controller for MouseController {
+    fn command(&mut self, _simulation: &Simulation, _side: Side, _delta_time: f32) -> PaddleInput {
+        PaddleInput {
+            movement: 0.0,
+            // The paddle stays put until the pointer has moved over the window
+            target: self.pointer_y,
+            release: std::mem::take(&mut self.release),
+        }
+    }
+
+    fn discard_pending(&mut self) {
+        self.release = false;
+    }
+
+    fn handle_event(&mut self, event: &InputEvent) {
+        match *event {
+            InputEvent::MouseMoved(position) => self.pointer_y = Some(position.y),
+            InputEvent::MouseDown(MouseButton::Left) => self.release = true,
+            _ => {}
+        }
+    }
+
+    // The player chose the mouse for this side, so a gamepad shouldn't take it away
+    fn yields_to_gamepad(&self) -> bool {
+        false
+    }
+
+    fn serve_prompt(&self) -> Option<String> {
+        Some("click".to_string())
+    }
+}
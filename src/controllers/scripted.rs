@@ -0,0 +1,29 @@
+use super::controller::Controller;
+use crate::entities::side::Side;
+use crate::simulation::{PaddleInput, Simulation};
+use std::collections::VecDeque;
+
+/// Plays back a fixed list of commands, one per simulation step, then stands still.
+/// Useful for tests, demos and replaying recorded input.
+pub struct ScriptedController {
+    commands: VecDeque<PaddleInput>,
+}
+
+impl ScriptedController {
+    pub fn new(commands: impl IntoIterator<Item = PaddleInput>) -> Self {
+        Self {
+            commands: commands.into_iter().collect(),
+        }
+    }
+
+    // Whether every command has been played
+    pub fn is_finished(&self) -> bool {
+        self.commands.is_empty()
+    }
+}
+
+impl Controller for ScriptedController {
+    fn command(&mut self, _simulation: &Simulation, _side: Side, _delta_time: f32) -> PaddleInput {
+        self.commands.pop_front().unwrap_or_default()
+    }
+}
@@ -0,0 +1,180 @@
+use ggez::glam::Vec2;
+use ggez::graphics::{self, Color, Drawable, Rect, Text, TextFragment};
+use ggez::input::keyboard::KeyCode;
+use ggez::{Context, GameResult};
+use pongpong::bindings::{key_name, Action, Bindings};
+use pongpong::config::CourtConfig;
+
+// Spacing of the action list
+const ROW_HEIGHT: f32 = 36.0;
+
+/// Opens and closes the controls screen. It can't be bound, since it never reaches the
+/// actions.
+pub const MENU_KEY: KeyCode = KeyCode::F1;
+
+/// The in-game screen for changing which keys do what. The menu's own keys (arrows, Enter,
+/// Backspace, F5 and Escape) always work, whatever the bindings say.
+pub struct ControlsMenu {
+    selected: usize, // Index into `Action::ALL`
+    capturing: bool, // Waiting for the key to add to the selected action
+}
+
+impl Default for ControlsMenu {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ControlsMenu {
+    pub fn new() -> Self {
+        Self {
+            selected: 0,
+            capturing: false,
+        }
+    }
+
+    fn selected_action(&self) -> Action {
+        Action::ALL[self.selected]
+    }
+
+    // Handles a key press while the screen is open. Returns false once the player closes it.
+    pub fn key_down(&mut self, key: KeyCode, bindings: &mut Bindings) -> bool {
+        if self.capturing {
+            // Escape backs out instead of being bound, and the menu key is kept for the menu.
+            // A key that can't be saved leaves the menu waiting for another.
+            self.capturing = false;
+            if key != KeyCode::Escape && key != MENU_KEY {
+                self.capturing = !bindings.bind(self.selected_action(), key);
+            }
+            return true;
+        }
+
+        let count = Action::ALL.len();
+        match key {
+            KeyCode::Up => self.selected = (self.selected + count - 1) % count,
+            KeyCode::Down => self.selected = (self.selected + 1) % count,
+            KeyCode::Return | KeyCode::NumpadEnter => self.capturing = true,
+            KeyCode::Back | KeyCode::Delete => bindings.clear(self.selected_action()),
+            KeyCode::F5 => *bindings = Bindings::default(),
+            KeyCode::Escape | MENU_KEY => return false,
+            _ => {}
+        }
+        true
+    }
+
+    pub fn draw(
+        &self,
+        ctx: &mut Context,
+        canvas: &mut graphics::Canvas,
+        bindings: &Bindings,
+        court: &CourtConfig,
+    ) -> GameResult {
+        // Cover the court so the list is easy to read
+        let panel = graphics::Mesh::new_rectangle(
+            ctx,
+            graphics::DrawMode::fill(),
+            Rect::new(0.0, 0.0, court.width, court.height),
+            Color::new(1.0, 1.0, 1.0, 0.95),
+        )?;
+        canvas.draw(&panel, graphics::DrawParam::default());
+
+        let title = Text::new(
+            TextFragment::new("CONTROLS")
+                .color(Color::BLACK)
+                .scale(graphics::PxScale::from(40.0)),
+        );
+        let title_width = title.dimensions(ctx).unwrap_or_default().w;
+        canvas.draw(
+            &title,
+            graphics::DrawParam::from(Vec2::new(court.width / 2.0 - title_width / 2.0, 40.0)),
+        );
+
+        let list_top = 110.0;
+        let name_x = court.width / 2.0 - 260.0;
+        let keys_x = court.width / 2.0 + 20.0;
+        for (index, action) in Action::ALL.iter().enumerate() {
+            let selected = index == self.selected;
+            let color = if selected {
+                Color::from_rgb(40, 110, 200)
+            } else {
+                Color::BLACK
+            };
+
+            let keys = if selected && self.capturing {
+                "press a key...".to_string()
+            } else if bindings.keys(*action).is_empty() {
+                "-".to_string()
+            } else {
+                bindings
+                    .keys(*action)
+                    .iter()
+                    .map(|&key| key_name(key))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            };
+
+            let y = list_top + index as f32 * ROW_HEIGHT;
+            let marker = if selected { "> " } else { "  " };
+            let name_text = Text::new(
+                TextFragment::new(format!("{}{}", marker, action.description()))
+                    .color(color)
+                    .scale(graphics::PxScale::from(24.0)),
+            );
+            let keys_text = Text::new(
+                TextFragment::new(keys)
+                    .color(color)
+                    .scale(graphics::PxScale::from(24.0)),
+            );
+            canvas.draw(&name_text, graphics::DrawParam::from(Vec2::new(name_x, y)));
+            canvas.draw(&keys_text, graphics::DrawParam::from(Vec2::new(keys_x, y)));
+        }
+
+        let help = Text::new(
+            TextFragment::new(
+                "Up/Down: choose   Enter: add a key   Backspace: clear   F5: defaults   Esc: save and close",
+            )
+            .color(Color::from_rgb(90, 90, 90))
+            .scale(graphics::PxScale::from(16.0)),
+        );
+        let help_width = help.dimensions(ctx).unwrap_or_default().w;
+        canvas.draw(
+            &help,
+            graphics::DrawParam::from(Vec2::new(
+                court.width / 2.0 - help_width / 2.0,
+                court.height - 50.0,
+            )),
+        );
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn captured_key_is_added_to_the_selected_action() {
+        let mut menu = ControlsMenu::new();
+        let mut bindings = Bindings::default();
+
+        assert!(menu.key_down(KeyCode::Return, &mut bindings));
+        assert!(menu.key_down(KeyCode::Q, &mut bindings));
+
+        assert!(bindings.is_bound(Action::ALL[0], KeyCode::Q));
+    }
+
+    #[test]
+    fn menu_key_cannot_be_bound() {
+        let mut menu = ControlsMenu::new();
+        let mut bindings = Bindings::default();
+
+        menu.key_down(KeyCode::Return, &mut bindings);
+        assert!(menu.key_down(MENU_KEY, &mut bindings));
+
+        assert!(Action::ALL
+            .iter()
+            .all(|&action| !bindings.is_bound(action, MENU_KEY)));
+        assert_eq!(bindings, Bindings::default());
+    }
+}
@@ -47,7 +47,13 @@ impl Countdown {
         false // Still counting down
     }
 
-    pub fn draw(&self, ctx: &mut Context, canvas: &mut graphics::Canvas) -> GameResult {
+    // Draws the current count centered on `center`
+    pub fn draw(
+        &self,
+        ctx: &mut Context,
+        canvas: &mut graphics::Canvas,
+        center: Vec2,
+    ) -> GameResult {
         if !self.active || self.count <= 0 {
             return Ok(());
         }
@@ -64,12 +70,11 @@ impl Countdown {
                 .scale(graphics::PxScale::from(100.0)),
         );
 
-        let screen_size = ctx.gfx.size();
         let text_dimensions = countdown_text.dimensions(ctx).unwrap_or_default();
 
         let position = Vec2::new(
-            screen_size.0 / 2.0 - text_dimensions.w / 2.0,
-            screen_size.1 / 2.0 - text_dimensions.h / 2.0,
+            center.x - text_dimensions.w / 2.0,
+            center.y - text_dimensions.h / 2.0,
         );
 
         canvas.draw(
@@ -1,7 +1,7 @@
 use ggez::glam::Vec2;
 use ggez::graphics::Color;
 use ggez::{graphics, Context, GameResult};
-use rand::{rng, Rng};
+use rand::Rng;
 use std::time::Duration;
 
 pub struct Particle {
@@ -14,9 +14,7 @@ pub struct Particle {
 }
 
 impl Particle {
-    pub fn new(position: Vec2, color: Color) -> Self {
-        let mut rng = rng();
-
+    pub fn new(position: Vec2, color: Color, rng: &mut impl Rng) -> Self {
         // Random velocity in circular pattern
         let angle = rng.random_range(0.0..std::f32::consts::TAU);
         let speed = rng.random_range(50.0..200.0);
@@ -65,13 +63,22 @@ impl ParticleSystem {
         }
     }
 
-    pub fn emit(&mut self, position: Vec2, color: Color, count: usize) {
+    pub fn emit(&mut self, position: Vec2, color: Color, count: usize, rng: &mut impl Rng) {
         for _ in 0..count {
             if self.particles.len() >= self.max_particles {
                 self.particles.remove(0); // Remove oldest particle
             }
 
-            self.particles.push(Particle::new(position, color));
+            self.particles.push(Particle::new(position, color, rng));
+        }
+    }
+
+    // Changes how many particles can be alive at once, dropping the oldest if needed
+    pub fn set_max_particles(&mut self, max_particles: usize) {
+        self.max_particles = max_particles;
+        if self.particles.len() > max_particles {
+            let excess = self.particles.len() - max_particles;
+            self.particles.drain(..excess);
         }
     }
 
@@ -1,53 +1,75 @@
-use crate::constants::{
-    BALL_COLOR, BALL_RADIUS, BALL_SPEED, MAX_BALL_SPEED, MOTION_BLUR_ENABLED,
-    MOTION_BLUR_TRAIL_COUNT,
-};
+use crate::config::BallConfig;
+use crate::entities::side::Side;
+use crate::physics::{self, Collider, Contact};
 use ggez::glam::Vec2;
-use ggez::graphics::Color;
-use rand::{rng, Rng};
+use ggez::graphics::{Color, Rect};
+use rand::Rng;
 use std::collections::VecDeque;
 use std::f32::consts::FRAC_PI_4;
 
+/// A ball resting on a paddle's face instead of moving freely, e.g. while waiting to be served
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Hold {
+    pub side: Side,   // Side of the paddle holding the ball
+    pub offset: f32,  // Distance of the ball's center below the paddle's center
+    pub rolls: bool,  // Whether the ball stays put while the paddle slides under it
+    pub time: f32,    // Seconds the ball has been held
+    pub caught: bool, // Caught mid-rally by a catch power-up rather than waiting to be served
+}
+
 pub struct Ball {
     pub position: Vec2,
+    pub previous_position: Vec2, // Position at the previous simulation tick, for interpolation
     pub velocity: Vec2,
     pub radius: f32,
     pub color: Color,
     pub previous_positions: VecDeque<Vec2>, // Store previous positions for motion blur
     pub speed_multiplier: f32,              // For power-ups and game variants
     pub base_speed: f32,                    // The base speed without multipliers
+    pub max_speed: f32,                     // Multipliers and acceleration stop here
+    pub uncapped_speed: Option<f32>,        // Speed the max speed is holding the ball back from
+    pub points: u32,                        // Points scored when this ball goes out
+    pub hold: Option<Hold>,                 // Set while a paddle is holding the ball
 }
 
 impl Ball {
-    pub fn new(position: Vec2) -> Self {
+    pub fn new(position: Vec2, config: &BallConfig, rng: &mut impl Rng) -> Self {
         // Generate random initial velocity using rng
-        let mut rng = rng();
         let angle = rng.random_range(-FRAC_PI_4..FRAC_PI_4);
         let direction = if rng.random_bool(0.5) { 1.0 } else { -1.0 };
 
-        let mut previous_positions = VecDeque::with_capacity(MOTION_BLUR_TRAIL_COUNT);
-        for _ in 0..MOTION_BLUR_TRAIL_COUNT {
+        // Without motion blur the trail is simply empty
+        let trail_count = if config.motion_blur_enabled {
+            config.motion_blur_trail_count
+        } else {
+            0
+        };
+        let mut previous_positions = VecDeque::with_capacity(trail_count);
+        for _ in 0..trail_count {
             previous_positions.push_back(position);
         }
 
         Ball {
             position,
-            radius: BALL_RADIUS,
-            color: BALL_COLOR,
+            previous_position: position,
+            radius: config.radius,
+            color: config.color,
             velocity: Vec2::new(
-                direction * angle.cos() * BALL_SPEED,
-                angle.sin() * BALL_SPEED,
+                direction * angle.cos() * config.speed,
+                angle.sin() * config.speed,
             ),
             previous_positions,
             speed_multiplier: 1.0,
-            base_speed: BALL_SPEED,
+            base_speed: config.speed,
+            max_speed: config.max_speed,
+            uncapped_speed: None,
+            points: 1,
+            hold: None,
         }
     }
 
     // Create a new ball as a split from an existing ball (used for multiball power-up)
-    pub fn split_from(original: &Ball) -> Self {
-        let mut rng = rng();
-
+    pub fn split_from(original: &Ball, rng: &mut impl Rng) -> Self {
         // Create a slight variation in angle for the split ball
         let angle_deviation = rng.random_range(-FRAC_PI_4..FRAC_PI_4);
         let current_angle = original.velocity.y.atan2(original.velocity.x);
@@ -55,114 +77,340 @@ impl Ball {
 
         let speed = original.velocity.length();
 
-        let mut previous_positions = VecDeque::with_capacity(MOTION_BLUR_TRAIL_COUNT);
-        for _ in 0..MOTION_BLUR_TRAIL_COUNT {
-            previous_positions.push_back(original.position);
-        }
+        let previous_positions = original
+            .previous_positions
+            .iter()
+            .map(|_| original.position)
+            .collect();
 
         Ball {
             position: original.position,
+            previous_position: original.position,
             radius: original.radius,
             color: original.color,
             velocity: Vec2::new(new_angle.cos() * speed, new_angle.sin() * speed),
             previous_positions,
             speed_multiplier: original.speed_multiplier,
             base_speed: original.base_speed,
+            max_speed: original.max_speed,
+            uncapped_speed: original.uncapped_speed,
+            points: original.points,
+            hold: None,
         }
     }
 
-    pub fn update(&mut self, delta_time: f32) {
+    // Records where the ball is before it moves this tick
+    pub fn begin_step(&mut self) {
+        self.previous_position = self.position;
+
         // Store the current position before updating for motion blur
-        if MOTION_BLUR_ENABLED {
-            self.previous_positions.pop_front();
+        if self.previous_positions.pop_front().is_some() {
             self.previous_positions.push_back(self.position);
         }
+    }
+
+    // Moves the ball for up to `time` seconds, stopping at the first collider it touches.
+    // Returns the contact so the caller can respond and spend the remaining time.
+    pub fn sweep(&mut self, time: f32, colliders: &[Collider]) -> Option<Contact> {
+        let motion = self.velocity * time;
 
-        // Update the ball's position using substepping for smoother movement
-        // This divides the update into smaller steps for better collision detection
-        const SUBSTEPS: usize = 3;
-        let sub_delta = delta_time / SUBSTEPS as f32;
+        let first_hit = colliders
+            .iter()
+            .filter_map(|collider| {
+                physics::sweep_circle_rect(self.position, self.radius, motion, collider.rect)
+                    .map(|hit| (collider, hit))
+            })
+            .min_by(|(_, a), (_, b)| a.time.total_cmp(&b.time));
 
-        for _ in 0..SUBSTEPS {
-            self.position += self.velocity * sub_delta;
+        match first_hit {
+            Some((collider, hit)) => {
+                self.position += motion * hit.time;
+                Some(Contact {
+                    collider: *collider,
+                    point: self.position - hit.normal * self.radius,
+                    normal: hit.normal,
+                    remaining_time: time * (1.0 - hit.time),
+                })
+            }
+            None => {
+                self.position += motion;
+                None
+            }
         }
     }
 
-    pub fn bounce_vertical(&mut self) {
-        // Reverse the vertical component of the velocity
-        self.velocity.y = -self.velocity.y;
+    // Pushes the ball out of any colliders it overlaps, in order, for example when a paddle
+    // moved into it. Returns the contacts so the caller can decide how to bounce.
+    pub fn resolve_overlaps(&mut self, colliders: &[Collider]) -> Vec<Contact> {
+        let mut contacts = Vec::new();
+
+        for collider in colliders {
+            if let Some(penetration) =
+                physics::circle_rect_penetration(self.position, self.radius, collider.rect)
+            {
+                self.position += penetration.normal * penetration.depth;
+
+                contacts.push(Contact {
+                    collider: *collider,
+                    point: self.position - penetration.normal * self.radius,
+                    normal: penetration.normal,
+                    remaining_time: 0.0,
+                });
+            }
+        }
+
+        contacts
     }
 
-    pub fn bounce_horizontal(&mut self) {
-        // Reverse the horizontal component of the velocity
-        self.velocity.x = -self.velocity.x;
+    // Pops the ball out of the side of a paddle it is still stuck in, which happens when
+    // it gets squeezed between a paddle edge and a wall and can't escape vertically
+    pub fn pop_out_sideways(&mut self, collider: &Collider) -> Option<Contact> {
+        physics::circle_rect_penetration(self.position, self.radius, collider.rect)?;
+
+        let rect = collider.rect;
+        let normal = if self.position.x < rect.x + rect.w / 2.0 {
+            self.position.x = rect.x - self.radius;
+            -Vec2::X
+        } else {
+            self.position.x = rect.x + rect.w + self.radius;
+            Vec2::X
+        };
+
+        Some(Contact {
+            collider: *collider,
+            point: self.position - normal * self.radius,
+            normal,
+            remaining_time: 0.0,
+        })
     }
 
-    pub fn reset(&mut self, position: Vec2) {
-        // Reset the ball's position and velocity
-        self.position = position;
+    // Bounces two overlapping balls off each other like equal-mass elastic spheres.
+    // Returns the point where they touched if they collided.
+    pub fn collide_with(&mut self, other: &mut Ball) -> Option<Vec2> {
+        let offset = other.position - self.position;
+        let distance = offset.length();
+        let min_distance = self.radius + other.radius;
 
-        // Clear motion blur trail
-        if MOTION_BLUR_ENABLED {
-            for pos in self.previous_positions.iter_mut() {
-                *pos = position;
-            }
+        if distance >= min_distance || distance <= 0.0 {
+            return None;
         }
 
-        let mut rng = rng();
-        let angle = rng.random_range(-FRAC_PI_4..FRAC_PI_4);
-        let direction = if rng.random_bool(0.5) { 1.0 } else { -1.0 };
+        // Balls that are already moving apart (like a fresh multiball split) are left alone
+        let normal = offset / distance;
+        let approach_speed = (self.velocity - other.velocity).dot(normal);
+        if approach_speed <= 0.0 {
+            return None;
+        }
+
+        // Separate them so they are just touching
+        let overlap = min_distance - distance;
+        self.position -= normal * (overlap / 2.0);
+        other.position += normal * (overlap / 2.0);
 
-        // Reset speed multiplier
-        self.speed_multiplier = 1.0;
-        self.base_speed = BALL_SPEED;
+        // Swap the parts of their velocities along the line between their centers
+        self.velocity -= normal * approach_speed;
+        other.velocity += normal * approach_speed;
 
-        self.velocity = Vec2::new(
-            direction * angle.cos() * self.base_speed * self.speed_multiplier,
-            angle.sin() * self.base_speed * self.speed_multiplier,
-        );
+        Some(self.position + normal * self.radius)
     }
 
-    // Apply a speed multiplier from a power-up or game mode
+    // Puts the ball on the face of a paddle, `offset` below its center, and keeps it there
+    pub fn hold_on(&mut self, paddle: Rect, side: Side, offset: f32, rolls: bool) {
+        self.position.y = paddle.y + paddle.h / 2.0 + offset;
+        self.hold = Some(Hold {
+            side,
+            offset,
+            rolls,
+            time: 0.0,
+            caught: false,
+        });
+        self.follow(paddle, 0.0);
+
+        // Don't draw a streak from wherever the ball was before
+        self.previous_position = self.position;
+        for pos in self.previous_positions.iter_mut() {
+            *pos = self.position;
+        }
+    }
+
+    // Stops a ball where it hit a sticky paddle. It rides along at that spot until released.
+    pub fn catch_on(&mut self, paddle: Rect, side: Side) {
+        let offset = self.position.y - (paddle.y + paddle.h / 2.0);
+        self.velocity = Vec2::ZERO;
+        self.hold_on(paddle, side, offset, false);
+        if let Some(hold) = &mut self.hold {
+            hold.caught = true;
+        }
+    }
+
+    // Keeps a held ball on its paddle's face. A rolling ball stays at the same height while
+    // the paddle slides under it, until one of the paddle's ends pushes it along.
+    pub fn follow(&mut self, paddle: Rect, delta_time: f32) {
+        let Some(hold) = &mut self.hold else {
+            return;
+        };
+
+        hold.time += delta_time;
+
+        let half_height = paddle.h / 2.0;
+        let paddle_center = paddle.y + half_height;
+        if hold.rolls {
+            hold.offset = self.position.y - paddle_center;
+        }
+        hold.offset = hold.offset.clamp(-half_height, half_height);
+
+        let x = match hold.side {
+            Side::Left => paddle.x + paddle.w + self.radius,
+            Side::Right => paddle.x - self.radius,
+        };
+        self.position = Vec2::new(x, paddle_center + hold.offset);
+    }
+
+    // Lets go of a held ball, sending it across the court at an angle that grows the further
+    // from the paddle's center it sat, up to `max_angle` radians at the paddle's ends
+    pub fn release(&mut self, paddle: Rect, max_angle: f32) {
+        let Some(hold) = self.hold.take() else {
+            return;
+        };
+
+        let angle = hold.offset / (paddle.h / 2.0) * max_angle;
+        let direction = match hold.side {
+            Side::Left => 1.0,
+            Side::Right => -1.0,
+        };
+        let speed = self.target_speed();
+
+        self.velocity = Vec2::new(direction * angle.cos() * speed, angle.sin() * speed);
+        self.uncapped_speed = None;
+    }
+
+    // Picks up new ball settings mid-rally. A moving ball keeps its direction and
+    // changes speed in proportion to the new base speed.
+    pub fn apply_config(&mut self, config: &BallConfig) {
+        if self.base_speed > 0.0 {
+            let new_speed = self.velocity.length() * config.speed / self.base_speed;
+            self.velocity = self.velocity.normalize_or_zero() * new_speed.min(config.max_speed);
+        }
+        self.uncapped_speed = None;
+
+        self.base_speed = config.speed;
+        self.max_speed = config.max_speed;
+        self.radius = config.radius;
+        self.color = config.color;
+    }
+
+    // The speed the ball settles at: its base speed with multipliers, up to its max speed
+    pub fn target_speed(&self) -> f32 {
+        (self.base_speed * self.speed_multiplier).min(self.max_speed)
+    }
+
+    // Apply a speed multiplier from a power-up or game mode. The ball's speed changes in
+    // proportion, so speed it picked up along the way (e.g. from acceleration) is kept.
     pub fn apply_speed_multiplier(&mut self, multiplier: f32) {
+        if multiplier == self.speed_multiplier {
+            return;
+        }
+
         let current_speed = self.velocity.length();
+        let new_speed = if self.speed_multiplier > 0.0 {
+            self.speed_before_cap() * multiplier / self.speed_multiplier
+        } else {
+            self.base_speed * multiplier
+        };
         self.speed_multiplier = multiplier;
 
-        // Ensure we don't exceed max speed
-        let new_speed = (self.base_speed * self.speed_multiplier).min(MAX_BALL_SPEED);
-
-        // Scale velocity to the new speed
         if current_speed > 0.0 {
-            self.velocity = self.velocity.normalize() * new_speed;
+            self.set_speed(new_speed);
         }
     }
 
     // Accelerate the ball based on game mode
     pub fn accelerate(&mut self, acceleration: f32, delta_time: f32) {
-        let current_speed = self.velocity.length();
+        let current_speed = self.speed_before_cap();
         let speed_increase = acceleration * delta_time;
 
-        // Calculate new speed, capped at maximum
-        let new_speed = (current_speed + speed_increase).min(MAX_BALL_SPEED);
+        // Acceleration stops at the max speed, though a speed-up can still hold the ball
+        // at the cap beyond that. Each step only adds a little, so even tiny increases are
+        // kept or a high tick rate would never speed the ball up.
+        let limit = self.max_speed * self.speed_multiplier.max(1.0);
+        let new_speed = (current_speed + speed_increase).min(limit);
+
+        if current_speed > 0.0 {
+            self.set_speed(new_speed);
+        }
+    }
+
+    // Sets the ball's speed, keeping its direction. Past the max speed the ball goes at the
+    // max but remembers the full speed, so a speed-up running out doesn't lose any speed.
+    fn set_speed(&mut self, speed: f32) {
+        self.velocity = self.velocity.normalize_or_zero() * speed.min(self.max_speed);
+        self.uncapped_speed = (speed > self.max_speed).then_some(speed);
+    }
 
-        // Only change if there's a meaningful difference
-        if (new_speed - current_speed).abs() > 0.1 && current_speed > 0.0 {
-            self.velocity = self.velocity.normalize() * new_speed;
+    // The ball's speed without the max speed cap. Anything else that changed the speed
+    // since it was capped, such as hitting another ball, wins over the remembered speed.
+    fn speed_before_cap(&self) -> f32 {
+        let speed = self.velocity.length();
+        match self.uncapped_speed {
+            Some(uncapped) if (speed - self.max_speed).abs() < 1.0 => uncapped,
+            _ => speed,
         }
     }
 
     // Normalize the velocity to maintain consistent speed
     pub fn normalize_velocity(&mut self) {
         let speed = self.velocity.length();
-        let target_speed = self.base_speed * self.speed_multiplier;
+        let target_speed = self.target_speed();
 
         if speed > 0.0 && (speed < target_speed * 0.9 || speed > target_speed * 1.1) {
             self.velocity = self.velocity.normalize() * target_speed;
         }
     }
 
+    // Position blended between the previous and current simulation tick for smooth rendering
+    pub fn interpolated_position(&self, alpha: f32) -> Vec2 {
+        self.previous_position.lerp(self.position, alpha)
+    }
+
     // Get the motion blur positions for rendering
     pub fn get_motion_blur_positions(&self) -> &VecDeque<Vec2> {
         &self.previous_positions
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng;
+
+    // A ball going right at `speed`, with the default 800 px/s max speed
+    fn ball_at(speed: f32) -> Ball {
+        let mut ball = Ball::new(Vec2::ZERO, &BallConfig::default(), &mut rng::seeded(1));
+        ball.velocity = Vec2::new(speed, 0.0);
+        ball
+    }
+
+    #[test]
+    fn speed_up_held_at_max_speed_gives_the_speed_back_when_it_ends() {
+        let mut ball = ball_at(700.0);
+
+        ball.apply_speed_multiplier(1.5);
+        assert_eq!(ball.velocity.length(), ball.max_speed);
+
+        ball.apply_speed_multiplier(1.0);
+        assert!((ball.velocity.length() - 700.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn acceleration_under_a_capped_speed_up_is_kept() {
+        let mut ball = ball_at(700.0);
+        ball.apply_speed_multiplier(1.5);
+
+        // 1050 px/s held at 800, then 50 px/s more while the cap hides it
+        ball.accelerate(50.0, 1.0);
+        assert_eq!(ball.velocity.length(), ball.max_speed);
+
+        ball.apply_speed_multiplier(1.0);
+        assert!((ball.velocity.length() - 1100.0 / 1.5).abs() < 0.01);
+    }
+}
@@ -4,3 +4,13 @@ pub enum Direction {
     Up,
     Down,
 }
+
+impl Direction {
+    /// The paddle movement for this direction at full speed: -1 is up, 1 is down
+    pub fn axis(self) -> f32 {
+        match self {
+            Direction::Up => -1.0,
+            Direction::Down => 1.0,
+        }
+    }
+}
@@ -0,0 +1,4 @@
+pub mod ball;
+pub mod direction;
+pub mod player;
+pub mod side;
@@ -1,46 +1,73 @@
-use ggez::{glam::*, graphics::Color};
+use ggez::{
+    glam::*,
+    graphics::{Color, Rect},
+};
 // use std::time::Duration;
 
-// constants
-use crate::constants::{PLAYER_SIZE, PLAYER_SPEED, SCREEN_SIZE};
-use crate::entities::direction::Direction;
+use crate::config::PaddleConfig;
 
 pub struct Player {
     pub position: Vec2,
+    pub previous_position: Vec2, // Position at the previous simulation tick, for interpolation
     pub size: Vec2,
     pub color: Color,
     pub speed_multiplier: f32,
     pub size_multiplier: f32,
     pub original_size: Vec2,
+    pub speed: f32,        // Base speed before power-ups
+    pub court_height: f32, // The paddle stays between 0 and this
 }
 
 impl Player {
-    pub fn new(color: Color, position: Vec2) -> Self {
+    pub fn new(color: Color, position: Vec2, config: &PaddleConfig, court_height: f32) -> Self {
         Self {
             color,
             position,
-            size: Vec2::new(PLAYER_SIZE.0, PLAYER_SIZE.1),
-            original_size: Vec2::new(PLAYER_SIZE.0, PLAYER_SIZE.1),
+            previous_position: position,
+            size: Vec2::new(config.width, config.height),
+            original_size: Vec2::new(config.width, config.height),
             speed_multiplier: 1.0,
             size_multiplier: 1.0,
+            speed: config.speed,
+            court_height,
         }
     }
 
-    pub fn update(&mut self, direction: Option<Direction>, delta_time: f32) {
-        if let Some(direction) = direction {
-            match direction {
-                Direction::Up => {
-                    if self.position.y > 0.0 {
-                        self.position.y -= PLAYER_SPEED * self.speed_multiplier * delta_time;
-                    }
-                }
-                Direction::Down => {
-                    if self.position.y + self.size.y < SCREEN_SIZE.1 {
-                        self.position.y += PLAYER_SPEED * self.speed_multiplier * delta_time;
-                    }
-                }
-            }
-        }
+    // Moves the paddle by `movement`, from -1 (full speed up) to 1 (full speed down)
+    pub fn update(&mut self, movement: f32, delta_time: f32) {
+        self.previous_position = self.position;
+        self.position.y += movement.clamp(-1.0, 1.0) * self.max_speed() * delta_time;
+
+        // Keep the paddle fully on the court so it never pokes into the walls
+        self.position.y = self.position.y.clamp(0.0, self.court_height - self.size.y);
+    }
+
+    // Moves the paddle's center toward `target_y` no faster than its top speed, so a paddle
+    // following the mouse can't jump across the court
+    pub fn move_toward(&mut self, target_y: f32, delta_time: f32) {
+        let full_step = self.max_speed() * delta_time;
+        let distance = target_y - (self.position.y + self.size.y / 2.0);
+        let movement = if full_step > 0.0 {
+            distance / full_step
+        } else {
+            0.0
+        };
+        self.update(movement, delta_time);
+    }
+
+    // Fastest the paddle can move right now, including power-ups
+    pub fn max_speed(&self) -> f32 {
+        self.speed * self.speed_multiplier
+    }
+
+    // The paddle's current bounds
+    pub fn rect(&self) -> Rect {
+        Rect::new(self.position.x, self.position.y, self.size.x, self.size.y)
+    }
+
+    // Position blended between the previous and current simulation tick for smooth rendering
+    pub fn interpolated_position(&self, alpha: f32) -> Vec2 {
+        self.previous_position.lerp(self.position, alpha)
     }
 
     // Apply a speed multiplier from a power-up
@@ -59,18 +86,26 @@ impl Player {
         let new_height = self.original_size.y * self.size_multiplier;
 
         // Ensure the paddle doesn't exceed screen bounds
-        let max_height = SCREEN_SIZE.1 * 0.8;
+        let max_height = self.court_height * 0.8;
         let clamped_height = new_height.min(max_height);
 
         // Update the size
         self.size.y = clamped_height;
 
         // Make sure the paddle is still in bounds
-        if self.position.y + self.size.y > SCREEN_SIZE.1 {
-            self.position.y = SCREEN_SIZE.1 - self.size.y;
+        if self.position.y + self.size.y > self.court_height {
+            self.position.y = self.court_height - self.size.y;
         }
     }
 
+    // Picks up new paddle settings mid-match, keeping any power-up multipliers
+    pub fn apply_config(&mut self, config: &PaddleConfig) {
+        self.speed = config.speed;
+        self.original_size = Vec2::new(config.width, config.height);
+        self.size.x = config.width;
+        self.update_size();
+    }
+
     // Reset power-up effects
     pub fn reset_power_ups(&mut self) {
         self.speed_multiplier = 1.0;
@@ -0,0 +1,1039 @@
+use ggez::graphics::Drawable;
+use ggez::{
+    event,
+    glam::*,
+    graphics::{self, Color, Rect, Text, TextFragment},
+    input::gamepad::{
+        gilrs::{Axis, Button},
+        GamepadId,
+    },
+    input::keyboard::KeyInput,
+    input::mouse::MouseButton,
+    Context, GameResult,
+};
+// Constants
+use crate::controls_menu::{ControlsMenu, MENU_KEY};
+use ggez::conf::FullscreenType;
+use pongpong::bindings::{key_name, Action, Bindings};
+use pongpong::config::{ConfigOverrides, GameConfig};
+use pongpong::config_watcher::ConfigWatcher;
+use pongpong::controllers::controller::{Controller, InputEvent};
+use pongpong::controllers::gamepad::{GamepadController, GamepadInput, PadAssigner};
+use pongpong::controllers::keyboard::KeyboardController;
+use pongpong::effects::{countdown::Countdown, particles::ParticleSystem};
+use pongpong::entities::{player::Player, side::Side};
+use pongpong::match_config::MatchConfig;
+use pongpong::powerups::definition::PowerUpDefinition;
+use pongpong::rng::{self, GameRng};
+use pongpong::rules::MatchState;
+use pongpong::simulation::{FrameInput, GameEvent, Simulation};
+use pongpong::viewport::Viewport;
+use rand::Rng;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Main game state structure: renders the simulation and feeds it each side's controller input.
+pub struct GameState {
+    simulation: Simulation,
+    controllers: [Box<dyn Controller>; 2], // Left, then right
+    pads: PadAssigner<GamepadId>,          // Which side each gamepad plays
+    paused_for_pad: bool,                  // Paused by an unplugged pad, so a new one resumes
+    // Visual effects
+    particle_system: ParticleSystem,
+    countdown: Countdown,
+    screen_shake: Option<(f32, Duration)>, // (intensity, remaining duration)
+    game_paused: bool,
+    round_in_progress: bool,
+    // Fixed timestep: frame time is banked and spent in whole simulation ticks
+    tick: Duration,
+    accumulator: Duration,
+    // Cosmetic randomness (particles, screen shake) is seeded separately so that
+    // drawing effects never changes how the simulated match plays out
+    effects_rng: GameRng,
+    // Live config reloading: the last reload error stays on screen until a good one loads
+    config_watcher: Option<ConfigWatcher>,
+    config_error: Option<String>,
+    // Everything is drawn in court units and scaled to the window
+    viewport: Viewport,
+    fullscreen: bool,
+    // Keys for each action, edited on the controls screen and saved to `bindings_path`
+    bindings: Bindings,
+    bindings_path: Option<PathBuf>,
+    controls_menu: Option<ControlsMenu>, // Open while the player is changing the controls
+}
+
+impl GameState {
+    /// Creates a new game state whose match is fully determined by `seed` and the player input.
+    pub fn new(
+        ctx: &mut Context,
+        game_config: GameConfig,
+        config: MatchConfig,
+        seed: u64,
+    ) -> GameResult<GameState> {
+        let tick_rate = game_config.simulation.tick_rate;
+        let court = game_config.court;
+        let window = ctx.gfx.drawable_size();
+        let bindings = Bindings::default();
+        let mut state = GameState {
+            simulation: Simulation::new(game_config, config, seed),
+            controllers: [
+                Box::new(KeyboardController::from_bindings(&bindings, Side::Left)),
+                Box::new(KeyboardController::from_bindings(&bindings, Side::Right)),
+            ],
+            pads: PadAssigner::new(),
+            paused_for_pad: false,
+            // Visual effects
+            particle_system: ParticleSystem::new(game_config.effects.max_particles),
+            countdown: Countdown::new(game_config.display.countdown_seconds),
+            screen_shake: None,
+            game_paused: false,
+            round_in_progress: false,
+            tick: tick_duration(tick_rate),
+            accumulator: Duration::ZERO,
+            effects_rng: rng::seeded(seed),
+            config_watcher: None,
+            config_error: None,
+            viewport: Viewport::new(
+                Vec2::new(court.width, court.height),
+                Vec2::new(window.0, window.1),
+            ),
+            fullscreen: false,
+            bindings,
+            bindings_path: None,
+            controls_menu: None,
+        };
+
+        // Start with a countdown
+        state.start_new_round();
+
+        Ok(state)
+    }
+
+    /// Switches between a window and filling the screen
+    pub fn set_fullscreen(&mut self, ctx: &mut Context, fullscreen: bool) -> GameResult {
+        let fullscreen_type = if fullscreen {
+            FullscreenType::Desktop
+        } else {
+            FullscreenType::Windowed
+        };
+        ctx.gfx.set_fullscreen(fullscreen_type)?;
+        self.fullscreen = fullscreen;
+        Ok(())
+    }
+
+    /// Hands the paddle on `side` to a different controller, e.g. the computer
+    pub fn set_controller(&mut self, side: Side, controller: Box<dyn Controller>) {
+        self.controllers[side.index()] = controller;
+    }
+
+    /// Spawns power-ups from `definitions` instead of the built-in ones
+    pub fn set_power_ups(&mut self, definitions: Vec<PowerUpDefinition>) {
+        self.simulation
+            .power_up_manager
+            .set_definitions(definitions);
+    }
+
+    /// Uses `bindings` for the keyboard, saving any changes made on the controls screen to
+    /// `save_path`
+    pub fn set_bindings(&mut self, bindings: Bindings, save_path: Option<PathBuf>) {
+        self.bindings = bindings;
+        self.bindings_path = save_path;
+        self.rebind_controllers();
+    }
+
+    fn rebind_controllers(&mut self) {
+        for (controller, side) in self.controllers.iter_mut().zip([Side::Left, Side::Right]) {
+            controller.rebind(&self.bindings, side);
+        }
+    }
+
+    // Opens the controls screen. The game pauses, and keys held until now are let go.
+    fn open_controls_menu(&mut self) {
+        if !self.countdown.active && !self.simulation.score.is_over() {
+            self.game_paused = true;
+        }
+        self.paused_for_pad = false;
+        self.send_to_controllers(InputEvent::FocusLost);
+        self.controls_menu = Some(ControlsMenu::new());
+    }
+
+    // Puts the edited bindings to use and saves them
+    fn close_controls_menu(&mut self) {
+        self.controls_menu = None;
+        self.send_to_controllers(InputEvent::FocusLost);
+        self.rebind_controllers();
+
+        if let Some(path) = &self.bindings_path {
+            if let Err(error) = self.bindings.save(path) {
+                eprintln!("{}", error);
+                self.config_error = Some(error.to_string());
+            }
+        }
+    }
+
+    // Passes window input on to both sides' controllers
+    fn send_to_controllers(&mut self, event: InputEvent) {
+        for controller in &mut self.controllers {
+            controller.handle_event(&event);
+        }
+    }
+
+    // Finds the side a gamepad plays, handing it a free side the first time it is seen.
+    // A pad taking over a side replaces whatever controller the side had, unless the
+    // computer is playing it.
+    fn assign_pad(&mut self, id: GamepadId) -> Option<Side> {
+        if let Some(side) = self.pads.side_of(id) {
+            self.pads.connect(id, &[side]);
+            return Some(side);
+        }
+
+        let open_sides: Vec<Side> = [Side::Left, Side::Right]
+            .into_iter()
+            .filter(|&side| self.controllers[side.index()].yields_to_gamepad())
+            .collect();
+        let side = self.pads.connect(id, &open_sides)?;
+        self.set_controller(side, Box::new(GamepadController::new()));
+        Some(side)
+    }
+
+    // Passes gamepad input on to the controller of the side the pad plays
+    fn send_to_pad_side(&mut self, id: GamepadId, input: GamepadInput) {
+        if let Some(side) = self.assign_pad(id) {
+            self.controllers[side.index()].handle_event(&InputEvent::Gamepad(input));
+        }
+    }
+
+    // Notices gamepads being plugged in and unplugged. An unplugged pad pauses the game
+    // until it, or another pad, is plugged back in.
+    fn poll_gamepads(&mut self, ctx: &Context) {
+        let plugged_in: Vec<GamepadId> = ctx
+            .gamepad
+            .gamepads()
+            .filter(|(_, pad)| pad.is_connected())
+            .map(|(id, _)| id)
+            .collect();
+
+        let unplugged: Vec<GamepadId> = self
+            .pads
+            .connected()
+            .filter(|id| !plugged_in.contains(id))
+            .collect();
+        for id in unplugged {
+            if let Some(side) = self.pads.disconnect(id) {
+                self.controllers[side.index()]
+                    .handle_event(&InputEvent::Gamepad(GamepadInput::Disconnected));
+                if !self.countdown.active && !self.game_paused {
+                    self.game_paused = true;
+                    self.paused_for_pad = true;
+                }
+            }
+        }
+
+        for id in plugged_in {
+            self.assign_pad(id);
+        }
+
+        // Play on once every side that lost its pad has one again
+        let waiting = [Side::Left, Side::Right]
+            .into_iter()
+            .any(|side| self.pads.is_waiting(side));
+        if self.paused_for_pad && !waiting {
+            self.paused_for_pad = false;
+            self.game_paused = false;
+        }
+    }
+
+    // Pauses or resumes play, except during the countdown and after the match
+    fn toggle_pause(&mut self) {
+        if !self.countdown.active && !self.simulation.score.is_over() {
+            self.game_paused = !self.game_paused;
+            self.paused_for_pad = false;
+        }
+    }
+
+    // Forgets serve presses made while the simulation isn't stepping
+    fn discard_pending_input(&mut self) {
+        for controller in &mut self.controllers {
+            controller.discard_pending();
+        }
+    }
+
+    // Asks both controllers what their paddles should do for the next tick
+    fn controller_input(&mut self) -> FrameInput {
+        let delta_time = self.tick.as_secs_f32();
+        let [left, right] = &mut self.controllers;
+        FrameInput {
+            left: left.command(&self.simulation, Side::Left, delta_time),
+            right: right.command(&self.simulation, Side::Right, delta_time),
+        }
+    }
+
+    /// Reloads the settings whenever the file at `path` is saved, keeping the command
+    /// line's `overrides`
+    pub fn watch_config(&mut self, path: impl Into<PathBuf>, overrides: ConfigOverrides) {
+        self.config_watcher = Some(ConfigWatcher::new(path, overrides));
+    }
+
+    // Applies a changed config file, or keeps the current settings and shows why it failed
+    fn reload_config(&mut self, delta: Duration) {
+        let Some(result) = self
+            .config_watcher
+            .as_mut()
+            .and_then(|watcher| watcher.poll(delta))
+        else {
+            return;
+        };
+
+        match result.and_then(|game_config| {
+            self.simulation.apply_game_config(game_config)?;
+            Ok(game_config)
+        }) {
+            Ok(game_config) => {
+                self.particle_system
+                    .set_max_particles(game_config.effects.max_particles);
+                self.countdown.duration =
+                    Duration::from_secs(game_config.display.countdown_seconds as u64);
+                self.tick = tick_duration(game_config.simulation.tick_rate);
+                self.config_error = None;
+            }
+            Err(error) => {
+                eprintln!("Keeping the previous settings: {}", error);
+                self.config_error = Some(error.to_string());
+            }
+        }
+    }
+
+    // Starts a new round with countdown, or a whole new match once the last one is over
+    fn start_new_round(&mut self) {
+        self.game_paused = true;
+        self.paused_for_pad = false;
+        self.round_in_progress = false;
+        self.countdown.start();
+        if self.simulation.score.is_over() {
+            self.simulation.restart_match();
+        } else {
+            self.simulation.start_new_round();
+        }
+    }
+
+    // Adds screen shake effect
+    fn add_screen_shake(&mut self, intensity: f32) {
+        let effects = self.simulation.game_config.effects;
+        if effects.screen_shake_enabled {
+            self.screen_shake = Some((
+                intensity,
+                Duration::from_secs_f32(effects.screen_shake_duration),
+            ));
+        }
+    }
+
+    // How far rendering is between the previous and the current simulation tick
+    fn interpolation_alpha(&self) -> f32 {
+        if self.game_paused {
+            return 1.0;
+        }
+        self.accumulator.as_secs_f32() / self.tick.as_secs_f32()
+    }
+
+    // Games and sets won, and the clock, when the match rules use them
+    fn match_status(&self) -> Option<String> {
+        let rules = &self.simulation.config.rules;
+        let score = &self.simulation.score;
+        let mut parts = Vec::new();
+
+        if rules.sets.is_some() {
+            parts.push(format!(
+                "Sets {}-{}   Games {}-{}",
+                score.yanga.sets, score.simba.sets, score.yanga.games, score.simba.games
+            ));
+        }
+
+        if let Some(time_limit) = rules.time_limit {
+            if score.state == MatchState::Overtime {
+                parts.push("GOLDEN GOAL".to_string());
+            } else {
+                let remaining = (time_limit.as_secs_f32() - self.simulation.game_time).max(0.0);
+                let seconds = remaining.ceil() as u32;
+                parts.push(format!("{}:{:02}", seconds / 60, seconds % 60));
+            }
+        }
+
+        (!parts.is_empty()).then(|| parts.join("   "))
+    }
+
+    // Cycles to the next game mode
+    fn cycle_game_mode(&mut self) {
+        let next_mode = self.simulation.config.game_mode.next();
+        self.simulation.set_game_mode(next_mode);
+        self.start_new_round(); // Reset the game for the new mode
+    }
+
+    // Turns simulation events into particles, screen shake and round transitions
+    fn handle_events(&mut self, events: &[GameEvent]) {
+        let effects = self.simulation.game_config.effects;
+        let mut should_shake = false;
+
+        for event in events {
+            match *event {
+                GameEvent::WallHit { position } => {
+                    if effects.particles_enabled {
+                        self.particle_system.emit(
+                            position,
+                            Color::new(0.8, 0.8, 0.8, 1.0),
+                            effects.wall_hit_particles,
+                            &mut self.effects_rng,
+                        );
+                    }
+                }
+                GameEvent::PaddleHit { side, position } => {
+                    if effects.particles_enabled {
+                        let color = self.simulation.player(side).color;
+                        self.particle_system.emit(
+                            position,
+                            color,
+                            effects.paddle_hit_particles,
+                            &mut self.effects_rng,
+                        );
+                    }
+                    should_shake = true;
+                }
+                GameEvent::Caught { side, position } => {
+                    // A soft landing: a puff in the player's color and no shake
+                    if effects.particles_enabled {
+                        let color = self.simulation.player(side).color;
+                        self.particle_system.emit(
+                            position,
+                            color,
+                            effects.paddle_hit_particles / 2,
+                            &mut self.effects_rng,
+                        );
+                    }
+                }
+                GameEvent::BallHit { position } => {
+                    if effects.particles_enabled {
+                        self.particle_system.emit(
+                            position,
+                            self.simulation.game_config.ball.color,
+                            effects.ball_hit_particles,
+                            &mut self.effects_rng,
+                        );
+                    }
+                    should_shake = true;
+                }
+                GameEvent::ShieldBroken { side, position } => {
+                    // The shield shatters where the ball hit it
+                    if effects.particles_enabled {
+                        let color = self.simulation.player(side).color;
+                        self.particle_system.emit(
+                            position,
+                            color,
+                            effects.shield_break_particles,
+                            &mut self.effects_rng,
+                        );
+                    }
+                    should_shake = true;
+                }
+                GameEvent::Served { .. } | GameEvent::Scored { .. } => {}
+                GameEvent::RoundOver => {
+                    // The simulation already reset the court, so just run the countdown
+                    self.game_paused = true;
+                    self.round_in_progress = false;
+                    self.countdown.start();
+                }
+                GameEvent::MatchOver { .. } => {
+                    // Play stops until the players start a new match
+                    self.game_paused = true;
+                    self.round_in_progress = false;
+                }
+            }
+        }
+
+        if should_shake {
+            self.add_screen_shake(effects.screen_shake_intensity);
+        }
+    }
+}
+
+impl event::EventHandler<ggez::GameError> for GameState {
+    // Updates the game state.
+    fn update(&mut self, ctx: &mut Context) -> GameResult {
+        let delta = ctx.time.delta();
+
+        // Pick up config file edits and gamepads coming and going before this frame's ticks
+        self.reload_config(delta);
+        self.poll_gamepads(ctx);
+        let settings = self.simulation.game_config;
+
+        // Everything waits while the controls screen is open, the countdown included
+        if self.controls_menu.is_some() {
+            self.accumulator = Duration::ZERO;
+            self.discard_pending_input();
+            return Ok(());
+        }
+
+        // Update countdown
+        if self.countdown.active && self.countdown.update(delta) {
+            // Countdown finished
+            self.game_paused = false;
+            self.round_in_progress = true;
+        }
+
+        // Update particles
+        if settings.effects.particles_enabled {
+            self.particle_system.update(delta);
+        }
+
+        // Update screen shake
+        if let Some((_intensity, remaining)) = &mut self.screen_shake {
+            let new_remaining = remaining.saturating_sub(delta);
+            if new_remaining.as_millis() == 0 {
+                self.screen_shake = None;
+            } else {
+                *remaining = new_remaining;
+            }
+        }
+
+        // Skip game logic updates if game is paused. Serve presses made meanwhile are dropped.
+        if self.game_paused {
+            self.accumulator = Duration::ZERO;
+            self.discard_pending_input();
+            return Ok(());
+        }
+
+        // Step the simulation in fixed ticks so gameplay doesn't depend on the frame rate
+        self.accumulator += delta.min(Duration::from_secs_f32(settings.simulation.max_frame_time));
+        while self.accumulator >= self.tick {
+            self.accumulator -= self.tick;
+
+            let input = self.controller_input();
+            let events = self.simulation.step(self.tick, &input);
+            self.handle_events(&events);
+
+            // A point was scored, so wait for the countdown before stepping again
+            if self.game_paused {
+                self.accumulator = Duration::ZERO;
+                break;
+            }
+        }
+
+        Ok(())
+    }
+
+    // Draws the game state.
+    fn draw(&mut self, ctx: &mut Context) -> GameResult {
+        // Bars fill whatever part of the window the court doesn't
+        let mut canvas = graphics::Canvas::from_frame(ctx, Color::BLACK);
+        let alpha = self.interpolation_alpha();
+        let settings = self.simulation.game_config;
+        let court = settings.court;
+        let display = settings.display;
+        let text_top = settings.paddle.padding;
+
+        // The court is scaled to fit the window, shaken if screen shake is active
+        let mut screen_offset = Vec2::ZERO;
+        if let Some((intensity, remaining)) = &self.screen_shake {
+            if settings.effects.screen_shake_enabled {
+                let shake_ratio = remaining.as_secs_f32() / settings.effects.screen_shake_duration;
+                screen_offset = Vec2::new(
+                    self.effects_rng.random_range(-*intensity..*intensity) * shake_ratio,
+                    self.effects_rng.random_range(-*intensity..*intensity) * shake_ratio,
+                );
+            }
+        }
+        let mut screen = self.viewport.screen_coordinates();
+        screen.translate(screen_offset);
+        canvas.set_screen_coordinates(screen);
+
+        let court_background = graphics::Mesh::new_rectangle(
+            ctx,
+            graphics::DrawMode::fill(),
+            Rect::new(0.0, 0.0, court.width, court.height),
+            Color::WHITE,
+        )?;
+        canvas.draw(&court_background, graphics::DrawParam::default());
+
+        // Draw scores on both sides of the players
+        let vs_text = graphics::Text::new(
+            graphics::TextFragment::new("VS")
+                .color(Color::BLACK)
+                .scale(graphics::PxScale::from(15.0)),
+        );
+        let yanga_score_text = graphics::Text::new(
+            graphics::TextFragment::new(format!("{}", self.simulation.score.yanga.points))
+                .color(Color::BLACK)
+                .scale(graphics::PxScale::from(30.0)),
+        );
+        let simba_score_text = graphics::Text::new(
+            graphics::TextFragment::new(format!("{}", self.simulation.score.simba.points))
+                .color(Color::BLACK)
+                .scale(graphics::PxScale::from(30.0)),
+        );
+
+        // Add the scores at the top of the screen with dramatic VS text
+        let yanga_score_position = Vec2::new(
+            court.width / 2.0
+                - yanga_score_text.dimensions(ctx).unwrap().w
+                - vs_text.dimensions(ctx).unwrap().w
+                - display.text_padding,
+            text_top,
+        );
+        let simba_score_position = Vec2::new(
+            court.width / 2.0 + vs_text.dimensions(ctx).unwrap().w + display.text_padding,
+            text_top,
+        );
+        let vs_position = Vec2::new(court.width / 2.0 - display.text_padding / 2.0, text_top);
+
+        // Draw the scores and the VS text
+        canvas.draw(
+            &yanga_score_text,
+            graphics::DrawParam::from(yanga_score_position).color(Color::BLACK),
+        );
+        canvas.draw(
+            &simba_score_text,
+            graphics::DrawParam::from(simba_score_position).color(Color::BLACK),
+        );
+        canvas.draw(
+            &vs_text,
+            graphics::DrawParam::from(vs_position).color(Color::BLACK),
+        );
+
+        // Draw games, sets and the clock under the scores
+        if let Some(status) = self.match_status() {
+            let status_text = Text::new(
+                TextFragment::new(status)
+                    .color(Color::BLACK)
+                    .scale(graphics::PxScale::from(18.0)),
+            );
+            let status_position = Vec2::new(
+                court.width / 2.0 - status_text.dimensions(ctx).unwrap().w / 2.0,
+                text_top + 40.0,
+            );
+
+            canvas.draw(
+                &status_text,
+                graphics::DrawParam::from(status_position).color(Color::BLACK),
+            );
+        }
+
+        // Draw the game mode text
+        let mode_text = Text::new(
+            TextFragment::new(format!("Mode: {}", self.simulation.config.game_mode))
+                .color(Color::BLACK)
+                .scale(graphics::PxScale::from(20.0)),
+        );
+
+        let mode_position = Vec2::new(10.0, court.height - 30.0);
+
+        canvas.draw(
+            &mode_text,
+            graphics::DrawParam::from(mode_position).color(Color::BLACK),
+        );
+
+        // Create player meshes. A paddle flashes while a power-up effect on it is running out.
+        let paddle_color = |player: &Player, player_index: usize| {
+            let mut color = player.color;
+            color.a = self
+                .simulation
+                .power_up_manager
+                .paddle_opacity(player_index);
+            color
+        };
+        let yanga_position = self.simulation.yanga_player.interpolated_position(alpha);
+        let yanga_player = graphics::Mesh::new_rectangle(
+            ctx,
+            graphics::DrawMode::fill(),
+            Rect::new(
+                yanga_position.x,
+                yanga_position.y,
+                self.simulation.yanga_player.size.x,
+                self.simulation.yanga_player.size.y,
+            ),
+            paddle_color(&self.simulation.yanga_player, 0),
+        )?;
+
+        let simba_position = self.simulation.simba_player.interpolated_position(alpha);
+        let simba_player = graphics::Mesh::new_rectangle(
+            ctx,
+            graphics::DrawMode::fill(),
+            Rect::new(
+                simba_position.x,
+                simba_position.y,
+                self.simulation.simba_player.size.x,
+                self.simulation.simba_player.size.y,
+            ),
+            paddle_color(&self.simulation.simba_player, 1),
+        )?;
+
+        // Draw the players
+        canvas.draw(&yanga_player, graphics::DrawParam::default());
+        canvas.draw(&simba_player, graphics::DrawParam::default());
+
+        // Draw shields as see-through bars in their player's color
+        for side in [Side::Left, Side::Right] {
+            if let Some(rect) = self.simulation.shield(side) {
+                let mut color = self.simulation.player(side).color;
+                color.a = 0.45;
+                let shield =
+                    graphics::Mesh::new_rectangle(ctx, graphics::DrawMode::fill(), rect, color)?;
+                canvas.draw(&shield, graphics::DrawParam::default());
+            }
+        }
+
+        // Draw each ball with motion blur
+        for ball in &self.simulation.balls {
+            // Draw motion blur trails if enabled
+            if settings.ball.motion_blur_enabled {
+                let trail_positions = ball.get_motion_blur_positions();
+
+                // Draw each trail with decreasing opacity
+                for (i, &pos) in trail_positions.iter().enumerate() {
+                    // Skip the most recent position as it will be drawn as the main ball
+                    if i == trail_positions.len() - 1 {
+                        continue;
+                    }
+
+                    // Calculate opacity based on position in the trail
+                    let opacity = 0.2 * (i as f32 / trail_positions.len() as f32);
+                    let trail_color = Color::new(ball.color.r, ball.color.g, ball.color.b, opacity);
+
+                    // Draw smaller circles for the trail
+                    let trail_radius =
+                        ball.radius * (0.7 + 0.3 * (i as f32 / trail_positions.len() as f32));
+
+                    let trail = graphics::Mesh::new_circle(
+                        ctx,
+                        graphics::DrawMode::fill(),
+                        pos,
+                        trail_radius,
+                        0.1,
+                        trail_color,
+                    )?;
+
+                    canvas.draw(&trail, graphics::DrawParam::default());
+                }
+            }
+
+            // Draw the ball
+            let ball_mesh = graphics::Mesh::new_circle(
+                ctx,
+                graphics::DrawMode::fill(),
+                ball.interpolated_position(alpha),
+                ball.radius,
+                0.1, // Reduce the tolerance for better performance
+                ball.color,
+            )?;
+
+            canvas.draw(&ball_mesh, graphics::DrawParam::default());
+
+            // Label balls that are worth more than one point
+            if ball.points > 1 {
+                let points_text = Text::new(
+                    TextFragment::new(ball.points.to_string())
+                        .color(Color::WHITE)
+                        .scale(graphics::PxScale::from(ball.radius * 1.6)),
+                );
+                let text_dimensions = points_text.dimensions(ctx).unwrap_or_default();
+
+                canvas.draw(
+                    &points_text,
+                    graphics::DrawParam::from(
+                        ball.interpolated_position(alpha)
+                            - Vec2::new(text_dimensions.w / 2.0, text_dimensions.h / 2.0),
+                    ),
+                );
+            }
+        }
+
+        // Draw center line - using dashed line for better visual effect
+        let center_x = court.width / 2.0;
+        let mut y = 0.0;
+
+        while y < court.height {
+            let dash_end = (y + display.center_line_dash_length).min(court.height);
+
+            let dash = graphics::Mesh::new_line(
+                ctx,
+                &[Vec2::new(center_x, y), Vec2::new(center_x, dash_end)],
+                display.center_line_width,
+                display.center_line_color,
+            )?;
+
+            canvas.draw(&dash, graphics::DrawParam::default());
+
+            // Move to the next dash position
+            y = dash_end + display.center_line_gap_length;
+        }
+
+        // Draw power-ups
+        if settings.power_ups.enabled {
+            self.simulation.power_up_manager.draw(ctx, &mut canvas)?;
+        }
+
+        // Draw particle effects
+        if settings.effects.particles_enabled {
+            self.particle_system.draw(ctx, &mut canvas)?;
+        }
+
+        // Draw countdown if active
+        if self.countdown.active {
+            self.countdown
+                .draw(ctx, &mut canvas, Vec2::new(court.width, court.height) / 2.0)?;
+        }
+
+        // Tell the server how to serve, or a catcher how to let go, while they hold the ball
+        let serving = self.simulation.balls.iter().find_map(|ball| ball.hold);
+        if let Some(hold) = serving.filter(|_| !self.game_paused) {
+            let name = match hold.side {
+                Side::Left => "Yanga",
+                Side::Right => "Simba",
+            };
+            let verb = if hold.caught { "release" } else { "serve" };
+            let prompt = self.controllers[hold.side.index()]
+                .serve_prompt()
+                .map(|how| format!("{} to {}: {}", name, verb, how))
+                .unwrap_or_default();
+            let serve_text = Text::new(
+                TextFragment::new(prompt)
+                    .color(self.simulation.player(hold.side).color)
+                    .scale(graphics::PxScale::from(20.0)),
+            );
+            let serve_position = Vec2::new(
+                court.width / 2.0 - serve_text.dimensions(ctx).unwrap().w / 2.0,
+                court.height - 30.0,
+            );
+
+            canvas.draw(&serve_text, graphics::DrawParam::from(serve_position));
+        }
+
+        // Show why the config file couldn't be reloaded
+        if let Some(error) = &self.config_error {
+            let error_text = Text::new(
+                TextFragment::new(format!("Config not reloaded: {}", error))
+                    .color(Color::new(0.8, 0.0, 0.0, 1.0))
+                    .scale(graphics::PxScale::from(16.0)),
+            );
+
+            canvas.draw(
+                &error_text,
+                graphics::DrawParam::from(Vec2::new(display.text_padding, text_top + 70.0)),
+            );
+        }
+
+        // Draw the winner once the match is over
+        if let Some(winner) = self.simulation.score.winner() {
+            let name = match winner {
+                Side::Left => "YANGA",
+                Side::Right => "SIMBA",
+            };
+            let winner_text = Text::new(
+                TextFragment::new(format!("{} WINS!", name))
+                    .color(self.simulation.player(winner).color)
+                    .scale(graphics::PxScale::from(50.0)),
+            );
+            let restart_prompt = match self.bindings.keys(Action::Restart).first() {
+                Some(&key) => format!("Press {} for a new match", key_name(key)),
+                None => format!(
+                    "Give Restart a key ({}) for a new match",
+                    key_name(MENU_KEY)
+                ),
+            };
+            let restart_text = Text::new(
+                TextFragment::new(restart_prompt)
+                    .color(Color::BLACK)
+                    .scale(graphics::PxScale::from(20.0)),
+            );
+
+            let winner_dimensions = winner_text.dimensions(ctx).unwrap();
+            let restart_dimensions = restart_text.dimensions(ctx).unwrap();
+            let winner_position = Vec2::new(
+                court.width / 2.0 - winner_dimensions.w / 2.0,
+                court.height / 2.0 - winner_dimensions.h,
+            );
+            let restart_position = Vec2::new(
+                court.width / 2.0 - restart_dimensions.w / 2.0,
+                court.height / 2.0 + display.text_padding,
+            );
+
+            canvas.draw(&winner_text, graphics::DrawParam::from(winner_position));
+            canvas.draw(&restart_text, graphics::DrawParam::from(restart_position));
+        }
+
+        // Draw paused text if the game is paused and countdown is not active
+        if self.game_paused && !self.countdown.active && !self.simulation.score.is_over() {
+            let paused_text = Text::new(
+                TextFragment::new("PAUSED") //TODO: maybe we add a nice pause icon?
+                    .color(Color::BLACK)
+                    .scale(graphics::PxScale::from(50.0)),
+            );
+
+            let text_dimensions = paused_text.dimensions(ctx).unwrap();
+            let paused_position = Vec2::new(
+                court.width / 2.0 - text_dimensions.w / 2.0,
+                court.height / 2.0 - text_dimensions.h / 2.0,
+            );
+
+            canvas.draw(
+                &paused_text,
+                graphics::DrawParam::from(paused_position).color(Color::BLACK),
+            );
+        }
+
+        // Ask for an unplugged gamepad to be plugged back in
+        let waiting: Vec<&str> = [(Side::Left, "Yanga"), (Side::Right, "Simba")]
+            .into_iter()
+            .filter(|&(side, _)| self.pads.is_waiting(side))
+            .map(|(_, name)| name)
+            .collect();
+        if !waiting.is_empty() {
+            let replug_text = Text::new(
+                TextFragment::new(format!(
+                    "Controller unplugged: plug it back in to play {}",
+                    waiting.join(" and ")
+                ))
+                .color(Color::from_rgb(200, 40, 40))
+                .scale(graphics::PxScale::from(20.0)),
+            );
+            let replug_position = Vec2::new(
+                court.width / 2.0 - replug_text.dimensions(ctx).unwrap().w / 2.0,
+                court.height / 2.0 + 50.0,
+            );
+            canvas.draw(&replug_text, graphics::DrawParam::from(replug_position));
+        }
+
+        if let Some(menu) = &self.controls_menu {
+            menu.draw(ctx, &mut canvas, &self.bindings, &court)?;
+        }
+
+        canvas.finish(ctx)?;
+
+        Ok(())
+    }
+
+    // Handles key press events.
+    fn key_down_event(&mut self, ctx: &mut Context, input: KeyInput, _repeat: bool) -> GameResult {
+        let Some(key_code) = input.keycode else {
+            return Ok(());
+        };
+
+        // The controls screen takes every key while it is open
+        if let Some(menu) = &mut self.controls_menu {
+            if !menu.key_down(key_code, &mut self.bindings) {
+                self.close_controls_menu();
+            }
+            return Ok(());
+        }
+        if key_code == MENU_KEY {
+            self.open_controls_menu();
+            return Ok(());
+        }
+
+        self.send_to_controllers(InputEvent::KeyDown(key_code));
+
+        if self.bindings.is_bound(Action::Pause, key_code) {
+            self.toggle_pause();
+        }
+        if self.bindings.is_bound(Action::Restart, key_code) {
+            // Restart the round, or the match once it is over
+            self.start_new_round();
+        }
+        if self.bindings.is_bound(Action::CycleMode, key_code) {
+            self.cycle_game_mode();
+        }
+        if self.bindings.is_bound(Action::Fullscreen, key_code) {
+            self.set_fullscreen(ctx, !self.fullscreen)?;
+        }
+
+        Ok(())
+    }
+
+    // Gamepad buttons go to the side the pad plays, except Start, which pauses
+    fn gamepad_button_down_event(
+        &mut self,
+        _ctx: &mut Context,
+        button: Button,
+        id: GamepadId,
+    ) -> GameResult {
+        if button == Button::Start {
+            self.toggle_pause();
+        } else {
+            self.send_to_pad_side(id, GamepadInput::ButtonDown(button));
+        }
+        Ok(())
+    }
+
+    fn gamepad_button_up_event(
+        &mut self,
+        _ctx: &mut Context,
+        button: Button,
+        id: GamepadId,
+    ) -> GameResult {
+        self.send_to_pad_side(id, GamepadInput::ButtonUp(button));
+        Ok(())
+    }
+
+    fn gamepad_axis_event(
+        &mut self,
+        _ctx: &mut Context,
+        axis: Axis,
+        value: f32,
+        id: GamepadId,
+    ) -> GameResult {
+        self.send_to_pad_side(id, GamepadInput::Axis(axis, value));
+        Ok(())
+    }
+
+    // Lets go of every held key when the window goes to the background
+    fn focus_event(&mut self, _ctx: &mut Context, gained: bool) -> GameResult {
+        if !gained {
+            self.send_to_controllers(InputEvent::FocusLost);
+        }
+        Ok(())
+    }
+
+    // The pointer is passed on in court units, wherever the court sits in the window
+    fn mouse_motion_event(
+        &mut self,
+        _ctx: &mut Context,
+        x: f32,
+        y: f32,
+        _dx: f32,
+        _dy: f32,
+    ) -> GameResult {
+        let position = self.viewport.to_court(Vec2::new(x, y));
+        self.send_to_controllers(InputEvent::MouseMoved(position));
+        Ok(())
+    }
+
+    fn mouse_button_down_event(
+        &mut self,
+        _ctx: &mut Context,
+        button: MouseButton,
+        _x: f32,
+        _y: f32,
+    ) -> GameResult {
+        if self.controls_menu.is_none() {
+            self.send_to_controllers(InputEvent::MouseDown(button));
+        }
+        Ok(())
+    }
+
+    // Keeps the court fitted to the window when it is resized or goes fullscreen
+    fn resize_event(&mut self, _ctx: &mut Context, width: f32, height: f32) -> GameResult {
+        self.viewport.window = Vec2::new(width, height);
+        Ok(())
+    }
+
+    // Handles key release events.
+    fn key_up_event(&mut self, _ctx: &mut Context, input: KeyInput) -> GameResult {
+        if let Some(key_code) = input.keycode {
+            self.send_to_controllers(InputEvent::KeyUp(key_code));
+        }
+
+        Ok(())
+    }
+}
+
+// Length of one simulation tick at the given rate
+fn tick_duration(tick_rate: u32) -> Duration {
+    Duration::from_secs_f64(1.0 / f64::from(tick_rate.max(1)))
+}
@@ -1,4 +1,5 @@
 use std::fmt;
+use std::str::FromStr;
 
 /// Different gameplay variants
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -38,3 +39,17 @@ impl fmt::Display for GameMode {
         write!(f, "{}", self.description())
     }
 }
+
+// Reads the names used on the command line
+impl FromStr for GameMode {
+    type Err = ();
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        match text {
+            "classic" => Ok(GameMode::Classic),
+            "accelerating" => Ok(GameMode::Accelerating),
+            "rally-fever" => Ok(GameMode::RallyFever),
+            _ => Err(()),
+        }
+    }
+}
@@ -0,0 +1,57 @@
+use crate::config::GameConfig;
+use crate::controllers::controller::Controller;
+use crate::entities::side::Side;
+use crate::match_config::MatchConfig;
+use crate::powerups::definition::PowerUpDefinition;
+use crate::rules::MatchScore;
+use crate::simulation::{FrameInput, GameEvent, Simulation};
+use std::time::Duration;
+
+/// How a match played without a window turned out
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MatchReport {
+    pub winner: Option<Side>, // None if the match was still going when time ran out
+    pub score: MatchScore,
+    pub game_time: f32, // Seconds of play
+    pub paddle_hits: u32,
+}
+
+/// Plays a match without a window as fast as possible, giving up after `max_game_time` of
+/// play so a match whose rules never end it still returns. The controllers are left, then right.
+pub fn play_match(
+    game_config: GameConfig,
+    config: MatchConfig,
+    power_ups: Vec<PowerUpDefinition>,
+    seed: u64,
+    mut controllers: [Box<dyn Controller>; 2],
+    max_game_time: Duration,
+) -> MatchReport {
+    let mut simulation = Simulation::new(game_config, config, seed);
+    simulation.power_up_manager.set_definitions(power_ups);
+    let tick = Duration::from_secs_f64(1.0 / f64::from(game_config.simulation.tick_rate.max(1)));
+
+    let mut winner = None;
+    let mut paddle_hits = 0;
+    while winner.is_none() && simulation.game_time < max_game_time.as_secs_f32() {
+        let [left, right] = &mut controllers;
+        let input = FrameInput {
+            left: left.command(&simulation, Side::Left, tick.as_secs_f32()),
+            right: right.command(&simulation, Side::Right, tick.as_secs_f32()),
+        };
+
+        for event in simulation.step(tick, &input) {
+            match event {
+                GameEvent::PaddleHit { .. } | GameEvent::Caught { .. } => paddle_hits += 1,
+                GameEvent::MatchOver { winner: side } => winner = Some(side),
+                _ => {}
+            }
+        }
+    }
+
+    MatchReport {
+        winner,
+        score: simulation.score,
+        game_time: simulation.game_time,
+        paddle_hits,
+    }
+}
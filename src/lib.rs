@@ -0,0 +1,35 @@
+//! Pong Pong game library.
+//!
+//! The rules, physics, controllers and settings shared by the windowed game in `main.rs` and
+//! its headless mode. Tools that want to run matches without a window can drive a
+//! [`Simulation`] directly.
+
+pub mod ai;
+pub mod bindings;
+pub mod config;
+pub mod config_watcher;
+pub mod constants;
+pub mod controllers;
+pub mod effects;
+pub mod entities;
+pub mod game_mode;
+pub mod headless;
+pub mod match_config;
+pub mod physics;
+pub mod powerups;
+pub mod rng;
+pub mod rules;
+pub mod simulation;
+pub mod viewport;
+
+pub use ai::{ComputerPlayer, Difficulty};
+pub use bindings::{Action, Bindings};
+pub use config::{ConfigError, GameConfig};
+pub use controllers::controller::{Controller, InputEvent};
+pub use entities::{ball::Ball, direction::Direction, player::Player, side::Side};
+pub use game_mode::GameMode;
+pub use headless::MatchReport;
+pub use match_config::{BallValues, MatchConfig, MultiballScoring, ServeRule};
+pub use powerups::manager::PowerUpManager;
+pub use rules::{MatchRules, MatchScore, MatchState, SetRules};
+pub use simulation::{FrameInput, GameEvent, PaddleInput, Simulation};
@@ -0,0 +1,74 @@
+use crate::game_mode::GameMode;
+use crate::rules::MatchRules;
+
+/// What happens when a ball leaves the court while several are in play
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MultiballScoring {
+    /// The first ball out scores and ends the round
+    FirstOut,
+
+    /// Every ball that goes out scores and is removed, and the round
+    /// only ends when the last ball is gone
+    Survival,
+}
+
+/// How many points a ball is worth when it goes out
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BallValues {
+    /// Every ball is worth one point
+    Uniform,
+
+    /// Each ball added by multiball is worth one point more than the last
+    Escalating,
+}
+
+/// Who starts each round, and how
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ServeRule {
+    /// The ball launches from the center in a random direction when the countdown ends
+    Random,
+
+    /// The player who lost the last point serves
+    LoserServes,
+
+    /// The serve changes sides every `every` points
+    Alternate { every: u32 },
+
+    /// The player who won the last point serves
+    WinnerServes,
+}
+
+/// Options chosen for a single match
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatchConfig {
+    /// Gameplay variant the match is played in
+    pub game_mode: GameMode,
+
+    /// Whether balls bounce off each other during multiball
+    pub ball_collisions: bool,
+
+    /// Scoring rule for rounds with more than one ball
+    pub multiball_scoring: MultiballScoring,
+
+    /// Points each ball is worth
+    pub ball_values: BallValues,
+
+    /// How the match is won
+    pub rules: MatchRules,
+
+    /// Who serves each round. Served balls are held on the paddle until released.
+    pub serve_rule: ServeRule,
+}
+
+impl Default for MatchConfig {
+    fn default() -> Self {
+        Self {
+            game_mode: GameMode::Classic,
+            ball_collisions: true,
+            multiball_scoring: MultiballScoring::FirstOut,
+            ball_values: BallValues::Uniform,
+            rules: MatchRules::default(),
+            serve_rule: ServeRule::Random,
+        }
+    }
+}
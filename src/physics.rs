@@ -0,0 +1,320 @@
+use crate::entities::side::Side;
+use ggez::glam::Vec2;
+use ggez::graphics::Rect;
+
+/// What a ball can bounce off
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Surface {
+    Wall,
+    Paddle(Side),
+    Shield(Side), // A power-up's barrier on that side's goal line
+}
+
+/// A solid rectangle the ball collides with
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Collider {
+    pub surface: Surface,
+    pub rect: Rect,
+    pub velocity: Vec2, // How fast the surface itself is moving
+}
+
+/// Where and when a moving circle first touches a rectangle
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SweepHit {
+    /// Fraction of the motion (0..=1) travelled before contact
+    pub time: f32,
+    /// Outward normal of the rectangle at the contact: a face normal, or pointing
+    /// away from the corner for corner hits
+    pub normal: Vec2,
+}
+
+/// How far and in which direction to push a circle out of a rectangle it overlaps
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Penetration {
+    pub normal: Vec2,
+    pub depth: f32,
+}
+
+/// A collision the ball ran into while moving
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Contact {
+    pub collider: Collider,
+    /// Point on the surface where the ball touched it
+    pub point: Vec2,
+    /// Outward normal of the surface at the contact
+    pub normal: Vec2,
+    /// Simulation time left in the step after the contact, in seconds
+    pub remaining_time: f32,
+}
+
+/// Sweeps a circle from `start` along `motion` and returns the first contact with `rect`.
+///
+/// The rectangle is grown by the circle's radius so the circle can be treated as a point:
+/// along the faces the grown shape is a box, and at the corners it is rounded. The point's
+/// path is clipped against the box one axis at a time, and paths that enter through a
+/// corner are then tested against that corner's circle. Circles that already overlap the
+/// rectangle are left to [`circle_rect_penetration`], and circles moving away from the
+/// surface they touch don't hit.
+pub fn sweep_circle_rect(start: Vec2, radius: f32, motion: Vec2, rect: Rect) -> Option<SweepHit> {
+    if circle_rect_penetration(start, radius, rect).is_some() {
+        return None;
+    }
+
+    let min = Vec2::new(rect.x - radius, rect.y - radius);
+    let max = Vec2::new(rect.x + rect.w + radius, rect.y + rect.h + radius);
+
+    let mut time_enter = f32::NEG_INFINITY;
+    let mut time_exit = f32::INFINITY;
+    let mut normal = Vec2::ZERO;
+
+    for (axis, unit) in [(0, Vec2::X), (1, Vec2::Y)] {
+        if motion[axis].abs() <= f32::EPSILON {
+            // Not moving along this axis, so we must already be within the slab
+            if start[axis] < min[axis] || start[axis] > max[axis] {
+                return None;
+            }
+            continue;
+        }
+
+        let time_min = (min[axis] - start[axis]) / motion[axis];
+        let time_max = (max[axis] - start[axis]) / motion[axis];
+        let (near, far, face_normal) = if time_min < time_max {
+            (time_min, time_max, -unit)
+        } else {
+            (time_max, time_min, unit)
+        };
+
+        if near > time_enter {
+            time_enter = near;
+            normal = face_normal;
+        }
+        time_exit = time_exit.min(far);
+    }
+
+    if time_enter > time_exit || time_exit < 0.0 || time_enter > 1.0 {
+        return None;
+    }
+
+    // Entering the box next to a corner means we might only clip its rounded edge, or miss it
+    let entry_point = start + motion * time_enter.max(0.0);
+    let beside_x = entry_point.x < rect.x || entry_point.x > rect.x + rect.w;
+    let beside_y = entry_point.y < rect.y || entry_point.y > rect.y + rect.h;
+    if beside_x && beside_y {
+        let corner = Vec2::new(
+            entry_point.x.clamp(rect.x, rect.x + rect.w),
+            entry_point.y.clamp(rect.y, rect.y + rect.h),
+        );
+        let time = sweep_point_circle(start, motion, corner, radius)?;
+        let normal = (start + motion * time - corner).normalize_or_zero();
+
+        if motion.dot(normal) >= 0.0 {
+            return None;
+        }
+        return Some(SweepHit { time, normal });
+    }
+
+    // Grazing along a face or leaving it isn't a hit
+    if time_enter < 0.0 || motion.dot(normal) >= 0.0 {
+        return None;
+    }
+
+    Some(SweepHit {
+        time: time_enter,
+        normal,
+    })
+}
+
+/// Returns how to push a circle out of `rect`, or `None` if they don't overlap
+pub fn circle_rect_penetration(center: Vec2, radius: f32, rect: Rect) -> Option<Penetration> {
+    let closest = Vec2::new(
+        center.x.clamp(rect.x, rect.x + rect.w),
+        center.y.clamp(rect.y, rect.y + rect.h),
+    );
+    let offset = center - closest;
+    let distance = offset.length();
+
+    if distance >= radius {
+        return None;
+    }
+
+    if distance > 0.0 {
+        return Some(Penetration {
+            normal: offset / distance,
+            depth: radius - distance,
+        });
+    }
+
+    // The center is inside the rectangle, so leave through the nearest face
+    let faces = [
+        (center.x - rect.x, -Vec2::X),
+        (rect.x + rect.w - center.x, Vec2::X),
+        (center.y - rect.y, -Vec2::Y),
+        (rect.y + rect.h - center.y, Vec2::Y),
+    ];
+    let (gap, normal) = faces
+        .into_iter()
+        .min_by(|(a, _), (b, _)| a.total_cmp(b))
+        .unwrap_or((0.0, Vec2::X));
+
+    Some(Penetration {
+        normal,
+        depth: gap + radius,
+    })
+}
+
+/// Returns when (0..=1) a point moving along `motion` first reaches a circle
+fn sweep_point_circle(start: Vec2, motion: Vec2, center: Vec2, radius: f32) -> Option<f32> {
+    let offset = start - center;
+    let a = motion.length_squared();
+    let b = 2.0 * motion.dot(offset);
+    let c = offset.length_squared() - radius * radius;
+
+    let discriminant = b * b - 4.0 * a * c;
+    if a <= f32::EPSILON || discriminant < 0.0 {
+        return None;
+    }
+
+    let time = (-b - discriminant.sqrt()) / (2.0 * a);
+    (0.0..=1.0).contains(&time).then_some(time)
+}
+
+/// Mirrors a velocity off a surface with the given unit normal
+pub fn reflect(velocity: Vec2, normal: Vec2) -> Vec2 {
+    velocity - 2.0 * velocity.dot(normal) * normal
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A thin paddle like the game's, 10 wide and 100 tall
+    const PADDLE: Rect = Rect {
+        x: 100.0,
+        y: 100.0,
+        w: 10.0,
+        h: 100.0,
+    };
+    const RADIUS: f32 = 5.0;
+
+    fn close(a: f32, b: f32) -> bool {
+        (a - b).abs() < 1e-3
+    }
+
+    #[test]
+    fn fast_ball_cannot_tunnel_through_a_thin_paddle() {
+        // Starts well left of the paddle and would end well past it in one step
+        let start = Vec2::new(0.0, 150.0);
+        let motion = Vec2::new(400.0, 0.0);
+
+        let hit = sweep_circle_rect(start, RADIUS, motion, PADDLE).expect("ball should hit");
+
+        // The ball's edge touches the left face at x = 100
+        assert!(close(start.x + motion.x * hit.time, PADDLE.x - RADIUS));
+        assert_eq!(hit.normal, -Vec2::X);
+    }
+
+    #[test]
+    fn hits_report_the_face_they_land_on() {
+        let from_above = sweep_circle_rect(
+            Vec2::new(105.0, 50.0),
+            RADIUS,
+            Vec2::new(0.0, 100.0),
+            PADDLE,
+        )
+        .expect("ball should hit the top");
+        assert_eq!(from_above.normal, -Vec2::Y);
+        assert!(close(from_above.time, 0.45));
+
+        let from_right = sweep_circle_rect(
+            Vec2::new(200.0, 150.0),
+            RADIUS,
+            Vec2::new(-100.0, 0.0),
+            PADDLE,
+        )
+        .expect("ball should hit the right face");
+        assert_eq!(from_right.normal, Vec2::X);
+    }
+
+    #[test]
+    fn corner_hit_pushes_away_from_the_corner() {
+        // Heads diagonally at the top-left corner
+        let start = Vec2::new(80.0, 80.0);
+        let motion = Vec2::new(20.0, 20.0);
+
+        let hit = sweep_circle_rect(start, RADIUS, motion, PADDLE).expect("ball should hit");
+
+        let contact = start + motion * hit.time;
+        assert!(close(
+            contact.distance(Vec2::new(PADDLE.x, PADDLE.y)),
+            RADIUS
+        ));
+        assert!(close(hit.normal.x, -std::f32::consts::FRAC_1_SQRT_2));
+        assert!(close(hit.normal.y, -std::f32::consts::FRAC_1_SQRT_2));
+    }
+
+    #[test]
+    fn ball_clipping_the_grown_box_beside_a_corner_only_hits_if_it_reaches_the_corner() {
+        // Both paths cross the top-left corner square of the rectangle grown by the radius
+        let motion = Vec2::new(12.0, -12.0);
+
+        // Passes about 6.4 from the corner: outside the rounded edge
+        assert!(sweep_circle_rect(Vec2::new(90.0, 101.0), RADIUS, motion, PADDLE).is_none());
+
+        // Passes about 4.2 from the corner: clips it
+        let hit = sweep_circle_rect(Vec2::new(90.0, 104.0), RADIUS, motion, PADDLE)
+            .expect("ball should clip the corner");
+        assert!(hit.normal.x < 0.0 && hit.normal.y < 0.0);
+    }
+
+    #[test]
+    fn moving_away_or_alongside_is_not_a_hit() {
+        let away = sweep_circle_rect(
+            Vec2::new(90.0, 150.0),
+            RADIUS,
+            Vec2::new(-50.0, 0.0),
+            PADDLE,
+        );
+        assert!(away.is_none());
+
+        // Sliding down the left face while just touching it
+        let grazing =
+            sweep_circle_rect(Vec2::new(95.0, 20.0), RADIUS, Vec2::new(0.0, 300.0), PADDLE);
+        assert!(grazing.is_none());
+    }
+
+    #[test]
+    fn ball_starting_inside_a_paddle_is_left_to_penetration() {
+        let center = Vec2::new(103.0, 150.0);
+        assert!(sweep_circle_rect(center, RADIUS, Vec2::new(50.0, 0.0), PADDLE).is_none());
+
+        // The center is 3 from the left face and 7 from the right, so it goes out left
+        let penetration = circle_rect_penetration(center, RADIUS, PADDLE).expect("overlap");
+        assert_eq!(penetration.normal, -Vec2::X);
+        assert!(close(penetration.depth, 3.0 + RADIUS));
+    }
+
+    #[test]
+    fn penetration_of_an_overlapping_edge() {
+        let penetration =
+            circle_rect_penetration(Vec2::new(113.0, 150.0), RADIUS, PADDLE).expect("overlap");
+        assert_eq!(penetration.normal, Vec2::X);
+        assert!(close(penetration.depth, 2.0));
+
+        assert!(circle_rect_penetration(Vec2::new(115.0, 150.0), RADIUS, PADDLE).is_none());
+    }
+
+    #[test]
+    fn point_reaches_circle_at_its_near_edge() {
+        let time = sweep_point_circle(Vec2::ZERO, Vec2::new(10.0, 0.0), Vec2::new(8.0, 0.0), 2.0);
+        assert!(close(time.expect("should reach"), 0.6));
+
+        // Too short a move, and a path that passes beside the circle
+        assert!(
+            sweep_point_circle(Vec2::ZERO, Vec2::new(5.0, 0.0), Vec2::new(8.0, 0.0), 2.0).is_none()
+        );
+        assert!(
+            sweep_point_circle(Vec2::ZERO, Vec2::new(10.0, 0.0), Vec2::new(5.0, 3.0), 2.0)
+                .is_none()
+        );
+    }
+}
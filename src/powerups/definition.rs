@@ -0,0 +1,356 @@
+use crate::config::{hex_color, invalid, non_negative, positive, ConfigError};
+use ggez::graphics::Color;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Power-up definitions read at startup when no `--power-ups` path is given
+pub const DEFAULT_POWER_UPS_PATH: &str = "powerups.toml";
+
+/// What collecting a power-up does
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PowerUpEffect {
+    PaddleSize,  // Multiplies the target's paddle height
+    PaddleSpeed, // Multiplies the target's paddle speed
+    BallSpeed,   // Multiplies the speed of balls the target sends
+    Multiball,   // Splits the ball into two
+    Shield,      // Puts a barrier on the target's goal line that stops one ball
+    Catch,       // Makes the target's paddle hold the balls it hits until they are released
+}
+
+impl PowerUpEffect {
+    // Whether the effect runs on a timer. The rest happen once when collected.
+    pub fn lasts(self) -> bool {
+        !matches!(self, PowerUpEffect::Multiball)
+    }
+}
+
+/// Who a power-up's effect lands on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Target {
+    Collector,
+    Opponent,
+}
+
+/// One kind of power-up: what it does, to whom, for how long, how often it appears and
+/// what it looks like
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PowerUpDefinition {
+    pub name: String,
+    pub effect: PowerUpEffect,
+    #[serde(default = "default_multiplier")]
+    pub multiplier: f32,
+    // Seconds the effect lasts, or `power_ups.duration` from the game config if not given
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration: Option<f32>,
+    // Balls a catch power-up holds before it wears off, or `power_ups.catch_hits` if not given
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hits: Option<u32>,
+    #[serde(default = "default_weight")]
+    pub weight: f32, // Chance of spawning, relative to the other power-ups
+    #[serde(with = "hex_color")]
+    pub color: Color,
+    #[serde(default = "default_target")]
+    pub target: Target,
+}
+
+fn default_multiplier() -> f32 {
+    1.0
+}
+
+fn default_weight() -> f32 {
+    1.0
+}
+
+fn default_target() -> Target {
+    Target::Collector
+}
+
+// The file is a list of `[[power_up]]` tables
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PowerUpFile {
+    #[serde(rename = "power_up", default)]
+    power_ups: Vec<PowerUpDefinition>,
+}
+
+impl PowerUpDefinition {
+    fn new(
+        name: &str,
+        effect: PowerUpEffect,
+        multiplier: f32,
+        color: Color,
+        target: Target,
+    ) -> Self {
+        Self {
+            name: name.to_string(),
+            effect,
+            multiplier,
+            duration: None,
+            hits: None,
+            weight: 1.0,
+            color,
+            target,
+        }
+    }
+
+    /// The power-ups used when there is no definitions file
+    pub fn defaults() -> Vec<Self> {
+        vec![
+            Self::new(
+                "paddle_grow",
+                PowerUpEffect::PaddleSize,
+                1.5,
+                Color::new(0.0, 0.8, 0.0, 1.0), // Green
+                Target::Collector,
+            ),
+            Self::new(
+                "paddle_shrink",
+                PowerUpEffect::PaddleSize,
+                0.75,
+                Color::new(0.8, 0.0, 0.0, 1.0), // Red
+                Target::Opponent,
+            ),
+            Self::new(
+                "speed_up",
+                PowerUpEffect::BallSpeed,
+                1.5,
+                Color::new(1.0, 0.5, 0.0, 1.0), // Orange
+                Target::Collector,
+            ),
+            Self::new(
+                "speed_down",
+                PowerUpEffect::BallSpeed,
+                0.75,
+                Color::new(0.0, 0.0, 0.8, 1.0), // Blue
+                Target::Opponent,
+            ),
+            Self::new(
+                "paddle_speed_up",
+                PowerUpEffect::PaddleSpeed,
+                1.5,
+                Color::new(0.0, 0.7, 0.7, 1.0), // Teal
+                Target::Collector,
+            ),
+            Self::new(
+                "paddle_slow_down",
+                PowerUpEffect::PaddleSpeed,
+                0.75,
+                Color::new(0.5, 0.5, 0.5, 1.0), // Grey
+                Target::Opponent,
+            ),
+            Self::new(
+                "multiball",
+                PowerUpEffect::Multiball,
+                1.0,
+                Color::new(0.8, 0.0, 0.8, 1.0), // Purple
+                Target::Collector,
+            ),
+            Self {
+                duration: Some(10.0),
+                ..Self::new(
+                    "shield",
+                    PowerUpEffect::Shield,
+                    1.0,
+                    Color::new(1.0, 0.85, 0.0, 1.0), // Gold
+                    Target::Collector,
+                )
+            },
+            Self {
+                duration: Some(15.0),
+                ..Self::new(
+                    "catch",
+                    PowerUpEffect::Catch,
+                    1.0,
+                    Color::new(1.0, 0.4, 0.7, 1.0), // Pink
+                    Target::Collector,
+                )
+            },
+        ]
+    }
+
+    /// Reads and validates a power-up definitions file
+    pub fn load(path: impl AsRef<Path>) -> Result<Vec<Self>, ConfigError> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path).map_err(|source| ConfigError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+
+        Self::parse(&text).map_err(|error| match error {
+            ConfigError::Parse { message, .. } => ConfigError::Parse {
+                path: Some(path.to_path_buf()),
+                message,
+            },
+            other => other,
+        })
+    }
+
+    /// Parses and validates power-up definitions
+    pub fn parse(text: &str) -> Result<Vec<Self>, ConfigError> {
+        let file: PowerUpFile = toml::from_str(text).map_err(|error| ConfigError::Parse {
+            path: None,
+            message: error.to_string(),
+        })?;
+        Self::validate_all(&file.power_ups)?;
+        Ok(file.power_ups)
+    }
+
+    /// The definitions as TOML, e.g. to start a definitions file from
+    pub fn to_toml(definitions: &[Self]) -> String {
+        let file = PowerUpFile {
+            power_ups: definitions.to_vec(),
+        };
+        toml::to_string_pretty(&file).unwrap_or_default()
+    }
+
+    /// Checks that every definition is usable and that at least one can spawn
+    pub fn validate_all(definitions: &[Self]) -> Result<(), ConfigError> {
+        for definition in definitions {
+            definition.validate().map_err(|error| match error {
+                ConfigError::Invalid { field, message } => ConfigError::Invalid {
+                    field,
+                    message: format!("{} (power-up \"{}\")", message, definition.name),
+                },
+                other => other,
+            })?;
+        }
+
+        if !definitions.iter().any(|definition| definition.weight > 0.0) {
+            return Err(invalid(
+                "power_up.weight",
+                "at least one power-up needs a weight above 0",
+            ));
+        }
+        Ok(())
+    }
+
+    fn validate(&self) -> Result<(), ConfigError> {
+        positive("power_up.multiplier", self.multiplier)?;
+        if let Some(duration) = self.duration {
+            positive("power_up.duration", duration)?;
+        }
+        if self.hits == Some(0) {
+            return Err(invalid("power_up.hits", "must be at least 1"));
+        }
+        non_negative("power_up.weight", self.weight)
+    }
+}
+
+// Picks a definition at random, each as likely as its weight allows
+pub fn pick(definitions: &[PowerUpDefinition], rng: &mut impl Rng) -> Option<usize> {
+    let total: f32 = definitions.iter().map(|definition| definition.weight).sum();
+    if total <= 0.0 {
+        return None;
+    }
+
+    let mut roll = rng.random_range(0.0..total);
+    for (index, definition) in definitions.iter().enumerate() {
+        if roll < definition.weight {
+            return Some(index);
+        }
+        roll -= definition.weight;
+    }
+
+    // Rounding can leave the roll just past the end
+    definitions
+        .iter()
+        .rposition(|definition| definition.weight > 0.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng;
+
+    fn weighted(weights: &[f32]) -> Vec<PowerUpDefinition> {
+        weights
+            .iter()
+            .map(|&weight| PowerUpDefinition {
+                weight,
+                ..PowerUpDefinition::defaults().remove(0)
+            })
+            .collect()
+    }
+
+    fn invalid_field(text: &str) -> Option<&'static str> {
+        match PowerUpDefinition::parse(text) {
+            Err(ConfigError::Invalid { field, .. }) => Some(field),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn picks_by_weight() {
+        let definitions = weighted(&[1.0, 0.0, 3.0]);
+        let mut rng = rng::seeded(7);
+        let mut counts = [0; 3];
+        for _ in 0..4000 {
+            counts[pick(&definitions, &mut rng).unwrap()] += 1;
+        }
+
+        assert_eq!(counts[1], 0, "a weight of 0 never spawns");
+        let share = counts[2] as f32 / 4000.0;
+        assert!(
+            (share - 0.75).abs() < 0.03,
+            "weight 3 of 4 spawned {}",
+            share
+        );
+    }
+
+    #[test]
+    fn picks_nothing_without_weight() {
+        let mut rng = rng::seeded(7);
+        assert_eq!(pick(&weighted(&[0.0, 0.0]), &mut rng), None);
+        assert_eq!(pick(&[], &mut rng), None);
+    }
+
+    #[test]
+    fn the_defaults_are_valid_and_round_trip() {
+        // Colors are saved to the nearest 1/255, so compare the saved text
+        let text = PowerUpDefinition::to_toml(&PowerUpDefinition::defaults());
+        let parsed = PowerUpDefinition::parse(&text).unwrap();
+        assert_eq!(PowerUpDefinition::to_toml(&parsed), text);
+    }
+
+    #[test]
+    fn rejects_a_file_where_nothing_can_spawn() {
+        let text = "
+            [[power_up]]
+            name = \"grow\"
+            effect = \"paddle_size\"
+            weight = 0.0
+            color = \"#00cc00\"
+        ";
+        assert_eq!(invalid_field(text), Some("power_up.weight"));
+    }
+
+    #[test]
+    fn rejects_a_negative_duration() {
+        let text = "
+            [[power_up]]
+            name = \"grow\"
+            effect = \"paddle_size\"
+            duration = -5.0
+            color = \"#00cc00\"
+        ";
+        assert_eq!(invalid_field(text), Some("power_up.duration"));
+    }
+
+    #[test]
+    fn rejects_an_unknown_effect() {
+        let text = "
+            [[power_up]]
+            name = \"teleport\"
+            effect = \"teleport\"
+            color = \"#00cc00\"
+        ";
+        assert!(matches!(
+            PowerUpDefinition::parse(text),
+            Err(ConfigError::Parse { .. })
+        ));
+    }
+}
@@ -0,0 +1,87 @@
+use super::definition::PowerUpEffect;
+use crate::config::PowerUpConfig;
+use crate::entities::player::Player;
+use std::time::Duration;
+
+/// A collected power-up's effect on one player, running until its timer runs out
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ActiveEffect {
+    pub source: usize, // Index of the power-up definition that caused it
+    pub effect: PowerUpEffect,
+    pub multiplier: f32,
+    pub remaining: Duration,
+    pub hits: Option<u32>, // Balls a catch effect can still hold; it wears off at 0
+}
+
+impl ActiveEffect {
+    pub fn new(source: usize, effect: PowerUpEffect, multiplier: f32, duration: f32) -> Self {
+        Self {
+            source,
+            effect,
+            multiplier,
+            remaining: Duration::from_secs_f32(duration.max(0.0)),
+            hits: None,
+        }
+    }
+
+    // Counts the timer down. Returns false once the effect has run out.
+    pub fn update(&mut self, delta: Duration) -> bool {
+        self.remaining = self.remaining.saturating_sub(delta);
+        !self.remaining.is_zero()
+    }
+
+    // Whether the effect is about to run out, so its paddle should flash
+    pub fn is_expiring(&self, config: &PowerUpConfig) -> bool {
+        self.remaining.as_secs_f32() <= config.flash_threshold
+    }
+}
+
+// The combined multiplier of one kind of effect. Effects of the same kind multiply together,
+// and no effects leave it at 1.
+pub fn multiplier(effects: &[ActiveEffect], kind: PowerUpEffect) -> f32 {
+    effects
+        .iter()
+        .filter(|effect| effect.effect == kind)
+        .map(|effect| effect.multiplier)
+        .product()
+}
+
+// Sets a paddle's multipliers from the effects running on its player
+pub fn apply_effects(player: &mut Player, effects: &[ActiveEffect]) {
+    player.apply_size_multiplier(multiplier(effects, PowerUpEffect::PaddleSize));
+    player.apply_speed_multiplier(multiplier(effects, PowerUpEffect::PaddleSpeed));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn runs_out_when_its_timer_does() {
+        let mut effect = ActiveEffect::new(0, PowerUpEffect::PaddleSize, 1.5, 1.0);
+        assert!(effect.update(Duration::from_millis(600)));
+        assert!(!effect.update(Duration::from_millis(600)));
+        assert!(effect.remaining.is_zero());
+    }
+
+    #[test]
+    fn flashes_once_it_is_about_to_run_out() {
+        let config = PowerUpConfig::default();
+        let mut effect = ActiveEffect::new(0, PowerUpEffect::PaddleSize, 1.5, 10.0);
+        assert!(!effect.is_expiring(&config));
+        effect.update(Duration::from_secs_f32(10.0 - config.flash_threshold));
+        assert!(effect.is_expiring(&config));
+    }
+
+    #[test]
+    fn effects_of_the_same_kind_multiply() {
+        let effects = [
+            ActiveEffect::new(0, PowerUpEffect::PaddleSize, 1.5, 10.0),
+            ActiveEffect::new(1, PowerUpEffect::PaddleSize, 0.5, 10.0),
+            ActiveEffect::new(2, PowerUpEffect::PaddleSpeed, 2.0, 10.0),
+        ];
+        assert_eq!(multiplier(&effects, PowerUpEffect::PaddleSize), 0.75);
+        assert_eq!(multiplier(&effects, PowerUpEffect::PaddleSpeed), 2.0);
+        assert_eq!(multiplier(&effects, PowerUpEffect::BallSpeed), 1.0);
+    }
+}
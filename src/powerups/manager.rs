@@ -1,33 +1,53 @@
-use super::powerup::{PowerUp, PowerUpType};
-use crate::constants::POWERUP_SPAWN_INTERVAL;
+use super::definition::{PowerUpDefinition, PowerUpEffect, Target};
+use super::effect::{self, apply_effects, ActiveEffect};
+use super::powerup::PowerUp;
+use crate::config::{CourtConfig, PowerUpConfig};
 use crate::entities::ball::Ball;
 use crate::entities::player::Player;
 // use ggez::glam::Vec2;
 use ggez::graphics;
 use ggez::{Context, GameResult};
+use rand::Rng;
 use std::time::Duration;
 
 pub struct PowerUpManager {
     power_ups: Vec<PowerUp>,
     spawn_timer: Duration,
-    total_time: f32, // Used for visual effects
+    total_time: f32,                     // Used for visual effects
+    multiball_requested: bool,           // Set when a multiball power-up is collected
+    effects: [Vec<ActiveEffect>; 2],     // Effects running on the left, then the right paddle
+    definitions: Vec<PowerUpDefinition>, // The kinds of power-up that can spawn
+    pub config: PowerUpConfig,
+    pub court: CourtConfig, // Power-ups spawn inside the court
+}
+
+impl Default for PowerUpManager {
+    fn default() -> Self {
+        Self::new(PowerUpConfig::default(), CourtConfig::default())
+    }
 }
 
 impl PowerUpManager {
-    pub fn new() -> Self {
+    pub fn new(config: PowerUpConfig, court: CourtConfig) -> Self {
         Self {
             power_ups: Vec::new(),
-            spawn_timer: Duration::from_secs_f32(POWERUP_SPAWN_INTERVAL),
+            spawn_timer: Duration::from_secs_f32(config.spawn_interval),
             total_time: 0.0,
+            multiball_requested: false,
+            effects: [Vec::new(), Vec::new()],
+            definitions: PowerUpDefinition::defaults(),
+            config,
+            court,
         }
     }
 
     pub fn update(
         &mut self,
         delta: Duration,
-        ball: &Ball,
+        balls: &[Ball],
         left_player: &mut Player,
         right_player: &mut Player,
+        rng: &mut impl Rng,
     ) {
         self.total_time += delta.as_secs_f32();
 
@@ -35,87 +55,308 @@ impl PowerUpManager {
         self.spawn_timer = self.spawn_timer.saturating_sub(delta);
         if self.spawn_timer.as_secs_f32() <= 0.0 {
             // Spawn a new power-up
-            self.spawn_power_up();
-            self.spawn_timer = Duration::from_secs_f32(POWERUP_SPAWN_INTERVAL);
+            self.spawn_power_up(rng);
+            self.spawn_timer = Duration::from_secs_f32(self.config.spawn_interval);
         }
 
         // Use a separate vector to track which power-ups were collected
         let mut collected_power_ups = Vec::new();
 
-        // Check for ball collisions with active power-ups
-        for (i, power_up) in self.power_ups.iter_mut().enumerate() {
-            if power_up.active && power_up.collides_with_ball(ball.position, ball.radius) {
+        // Check for ball collisions with active power-ups. Any ball in play can collect
+        // them; one held on a paddle isn't heading anywhere, so it can't.
+        for power_up in &mut self.power_ups {
+            let collector = balls.iter().find(|ball| {
+                power_up.active
+                    && ball.hold.is_none()
+                    && power_up.collides_with_ball(ball.position, ball.radius)
+            });
+            if let Some(ball) = collector {
                 // Determine which player gets the power-up based on ball direction
                 let player_index = if ball.velocity.x > 0.0 { 1 } else { 0 };
                 power_up.activate(player_index);
 
                 // Store which power-up was collected for later processing
-                collected_power_ups.push((i, power_up.power_type, player_index));
+                collected_power_ups.push((power_up.definition, player_index));
             }
         }
 
-        // Apply power-up effects after the loop
-        for (_, power_type, player_index) in collected_power_ups {
-            match power_type {
-                PowerUpType::PaddleGrow => {
-                    if player_index == 0 {
-                        left_player.apply_size_multiplier(1.5);
-                    } else {
-                        right_player.apply_size_multiplier(1.5);
-                    }
-                }
-                PowerUpType::PaddleShrink => {
-                    if player_index == 0 {
-                        right_player.apply_size_multiplier(0.75);
-                    } else {
-                        left_player.apply_size_multiplier(0.75);
-                    }
-                }
-                PowerUpType::SpeedUp => {
-                    if player_index == 0 {
-                        left_player.apply_speed_multiplier(1.5);
-                    } else {
-                        right_player.apply_speed_multiplier(1.5);
-                    }
-                }
-                PowerUpType::SpeedDown => {
-                    if player_index == 0 {
-                        right_player.apply_speed_multiplier(0.75);
-                    } else {
-                        left_player.apply_speed_multiplier(0.75);
-                    }
-                }
-                PowerUpType::MultiballSplit => {
-                    // This is handled in the main game logic
+        // Start the effects after the loop, on the collector or their opponent
+        for (index, player_index) in collected_power_ups {
+            let definition = &self.definitions[index];
+            let target_index = match definition.target {
+                Target::Collector => player_index,
+                Target::Opponent => 1 - player_index,
+            };
+
+            if definition.effect.lasts() {
+                let duration = definition.duration.unwrap_or(self.config.duration);
+                let mut effect =
+                    ActiveEffect::new(index, definition.effect, definition.multiplier, duration);
+                if definition.effect == PowerUpEffect::Catch {
+                    effect.hits = Some(definition.hits.unwrap_or(self.config.catch_hits));
                 }
+                self.add_effect(target_index, effect);
+            } else {
+                // The split itself is handled in the main game logic
+                self.multiball_requested = true;
             }
         }
 
-        // Update active power-ups and remove expired ones
-        self.power_ups.retain_mut(|power_up| power_up.update(delta));
+        // Collected power-ups leave the court; their effects live on in the effect lists
+        self.power_ups.retain_mut(|power_up| {
+            power_up.update(delta);
+            power_up.active
+        });
+
+        // Run the effect timers and put the paddles in line with whatever is still running
+        for effects in &mut self.effects {
+            effects.retain_mut(|effect| effect.update(delta));
+        }
+        apply_effects(left_player, &self.effects[0]);
+        apply_effects(right_player, &self.effects[1]);
+    }
+
+    // Starts an effect on a player's paddle. Collecting a power-up whose effect is already
+    // running on that paddle restarts its timer instead of doubling it up; different effects
+    // stack, each running out on its own timer.
+    fn add_effect(&mut self, player_index: usize, effect: ActiveEffect) {
+        let effects = &mut self.effects[player_index];
+        match effects
+            .iter_mut()
+            .find(|running| running.source == effect.source)
+        {
+            Some(running) => *running = effect,
+            None => effects.push(effect),
+        }
+    }
+
+    // The effects running on a player's paddle: 0 for the left player, 1 for the right
+    pub fn effects(&self, player_index: usize) -> &[ActiveEffect] {
+        &self.effects[player_index]
+    }
+
+    // The speed multiplier for a ball moving with horizontal velocity `velocity_x`. Ball speed
+    // effects belong to the player who sends the ball, so they only change balls heading
+    // toward that player's opponent.
+    pub fn ball_speed_multiplier(&self, velocity_x: f32) -> f32 {
+        let sender_index = if velocity_x > 0.0 {
+            0
+        } else if velocity_x < 0.0 {
+            1
+        } else {
+            return 1.0;
+        };
+        effect::multiplier(&self.effects[sender_index], PowerUpEffect::BallSpeed)
+    }
+
+    // Whether a player's goal line is guarded by a shield
+    pub fn has_shield(&self, player_index: usize) -> bool {
+        self.effects[player_index]
+            .iter()
+            .any(|effect| effect.effect == PowerUpEffect::Shield)
+    }
+
+    // Breaks a player's shield once it has stopped a ball
+    pub fn break_shield(&mut self, player_index: usize) {
+        self.effects[player_index].retain(|effect| effect.effect != PowerUpEffect::Shield);
+    }
+
+    // Uses up one of a player's catches when their paddle holds a ball. Returns false if
+    // the player has no catch running, so the ball should bounce as usual.
+    pub fn use_catch(&mut self, player_index: usize) -> bool {
+        let effects = &mut self.effects[player_index];
+        let Some(index) = effects
+            .iter()
+            .position(|effect| effect.effect == PowerUpEffect::Catch)
+        else {
+            return false;
+        };
+
+        let hits = effects[index].hits.get_or_insert(1);
+        *hits = hits.saturating_sub(1);
+        if *hits == 0 {
+            effects.remove(index);
+        }
+        true
+    }
+
+    // How opaque to draw a player's paddle: it flashes while one of its effects is about
+    // to run out
+    pub fn paddle_opacity(&self, player_index: usize) -> f32 {
+        let expiring = self.effects[player_index]
+            .iter()
+            .any(|effect| effect.is_expiring(&self.config));
+        if expiring {
+            // Flash by alternating opacity based on time
+            let flash_cycle = (self.total_time * self.config.flash_speed).sin() * 0.5 + 0.5;
+            return 0.5 + flash_cycle * 0.5; // Oscillate between 0.5 and 1.0
+        }
+        1.0 // Full opacity
+    }
+
+    fn spawn_power_up(&mut self, rng: &mut impl Rng) {
+        let power_up = PowerUp::random(rng, &self.definitions, &self.config, &self.court);
+        self.power_ups.extend(power_up);
+    }
+
+    // The kinds of power-up that can spawn
+    pub fn definitions(&self) -> &[PowerUpDefinition] {
+        &self.definitions
     }
 
-    fn spawn_power_up(&mut self) {
-        self.power_ups.push(PowerUp::random());
+    // Replaces the kinds of power-up that can spawn. Power-ups on the court and running
+    // effects belong to the old definitions, so they are cleared.
+    pub fn set_definitions(&mut self, definitions: Vec<PowerUpDefinition>) {
+        self.definitions = definitions;
+        self.power_ups.clear();
+        self.effects = [Vec::new(), Vec::new()];
     }
 
     pub fn draw(&self, ctx: &mut Context, canvas: &mut graphics::Canvas) -> GameResult {
         for power_up in &self.power_ups {
-            power_up.draw(ctx, canvas, self.total_time)?;
+            power_up.draw(ctx, canvas)?;
         }
         Ok(())
     }
 
-    pub fn get_active_multiball(&self) -> bool {
-        self.power_ups.iter().any(|p| {
-            p.power_type == PowerUpType::MultiballSplit
-                && p.collected_by.is_some()
-                && p.remaining_duration.is_some()
-        })
+    // Power-ups waiting on the court to be collected
+    pub fn power_ups(&self) -> &[PowerUp] {
+        &self.power_ups
+    }
+
+    // Puts a power-up on the court at a known spot instead of waiting for one to spawn
+    #[cfg(test)]
+    pub fn place(&mut self, power_up: PowerUp) {
+        self.power_ups.push(power_up);
+    }
+
+    // Returns whether a multiball power-up was collected since the last call
+    pub fn take_multiball_request(&mut self) -> bool {
+        std::mem::take(&mut self.multiball_requested)
+    }
+
+    // Picks up new power-up settings. A shorter spawn interval takes effect right away.
+    pub fn apply_config(&mut self, config: PowerUpConfig) {
+        self.config = config;
+        self.spawn_timer = self
+            .spawn_timer
+            .min(Duration::from_secs_f32(config.spawn_interval));
     }
 
     pub fn reset(&mut self) {
         self.power_ups.clear();
-        self.spawn_timer = Duration::from_secs_f32(POWERUP_SPAWN_INTERVAL);
+        self.effects = [Vec::new(), Vec::new()];
+        self.multiball_requested = false;
+        self.spawn_timer = Duration::from_secs_f32(self.config.spawn_interval);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{BallConfig, PaddleConfig};
+    use crate::rng;
+    use ggez::glam::Vec2;
+    use ggez::graphics::Color;
+
+    fn players() -> (Player, Player) {
+        let config = PaddleConfig::default();
+        let court = CourtConfig::default();
+        let left = Player::new(Color::WHITE, Vec2::new(30.0, 300.0), &config, court.height);
+        let right = Player::new(Color::WHITE, Vec2::new(770.0, 300.0), &config, court.height);
+        (left, right)
+    }
+
+    fn ball(position: Vec2, velocity: Vec2) -> Ball {
+        let mut ball = Ball::new(position, &BallConfig::default(), &mut rng::seeded(1));
+        ball.velocity = velocity;
+        ball
+    }
+
+    #[test]
+    fn any_free_ball_collects_a_power_up() {
+        let mut manager = PowerUpManager::default();
+        let color = manager.definitions[0].color;
+        manager.place(PowerUp::new(Vec2::new(400.0, 300.0), 0, color, 20.0));
+        let balls = [
+            ball(Vec2::new(100.0, 100.0), Vec2::new(200.0, 0.0)),
+            ball(Vec2::new(400.0, 300.0), Vec2::new(-200.0, 0.0)),
+        ];
+        let (mut left, mut right) = players();
+
+        let delta = Duration::from_millis(16);
+        manager.update(delta, &balls, &mut left, &mut right, &mut rng::seeded(1));
+
+        assert!(manager.power_ups().is_empty());
+        assert_eq!(manager.effects(0).len(), 1);
+        assert!(manager.effects(1).is_empty());
+    }
+
+    #[test]
+    fn collecting_a_running_effect_restarts_its_timer() {
+        let mut manager = PowerUpManager::default();
+        manager.add_effect(
+            0,
+            ActiveEffect::new(0, PowerUpEffect::PaddleSize, 1.5, 10.0),
+        );
+        manager.effects[0][0].update(Duration::from_secs(8));
+        manager.add_effect(
+            0,
+            ActiveEffect::new(0, PowerUpEffect::PaddleSize, 1.5, 10.0),
+        );
+
+        assert_eq!(manager.effects(0).len(), 1);
+        assert_eq!(manager.effects(0)[0].remaining, Duration::from_secs(10));
+    }
+
+    #[test]
+    fn different_power_ups_stack_and_run_out_on_their_own() {
+        let mut manager = PowerUpManager::default();
+        manager.add_effect(0, ActiveEffect::new(0, PowerUpEffect::PaddleSize, 1.5, 2.0));
+        manager.add_effect(0, ActiveEffect::new(1, PowerUpEffect::PaddleSize, 0.5, 5.0));
+        let (mut left, mut right) = players();
+        let mut rng = rng::seeded(1);
+
+        let delta = Duration::from_secs(1);
+        manager.update(delta, &[], &mut left, &mut right, &mut rng);
+        assert_eq!(manager.effects(0).len(), 2);
+        assert_eq!(left.size.y, left.original_size.y * 0.75);
+
+        manager.update(delta, &[], &mut left, &mut right, &mut rng);
+        assert_eq!(manager.effects(0).len(), 1);
+        assert_eq!(left.size.y, left.original_size.y * 0.5);
+        assert_eq!(right.size.y, right.original_size.y);
+    }
+
+    #[test]
+    fn a_catch_wears_off_after_its_hits() {
+        let mut manager = PowerUpManager::default();
+        assert!(!manager.use_catch(1));
+
+        let mut catch = ActiveEffect::new(0, PowerUpEffect::Catch, 1.0, 15.0);
+        catch.hits = Some(2);
+        manager.add_effect(1, catch);
+        assert!(!manager.use_catch(0));
+        assert!(manager.use_catch(1));
+        assert_eq!(manager.effects(1)[0].hits, Some(1));
+        assert!(manager.use_catch(1));
+        assert!(manager.effects(1).is_empty());
+        assert!(!manager.use_catch(1));
+    }
+
+    #[test]
+    fn a_shield_breaks_once() {
+        let mut manager = PowerUpManager::default();
+        manager.add_effect(0, ActiveEffect::new(0, PowerUpEffect::Shield, 1.0, 10.0));
+        manager.add_effect(
+            0,
+            ActiveEffect::new(1, PowerUpEffect::PaddleSize, 1.5, 10.0),
+        );
+        assert!(manager.has_shield(0));
+        assert!(!manager.has_shield(1));
+
+        manager.break_shield(0);
+        assert!(!manager.has_shield(0));
+        assert_eq!(manager.effects(0).len(), 1);
     }
 }
@@ -1,2 +1,4 @@
+pub mod definition;
+pub mod effect;
 pub mod manager;
 pub mod powerup;
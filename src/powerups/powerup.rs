@@ -1,162 +1,88 @@
-use crate::constants::{
-    POWERUP_DURATION, POWERUP_FLASH_SPEED, POWERUP_FLASH_THRESHOLD, POWERUP_SIZE, SCREEN_SIZE,
-};
+use super::definition::{self, PowerUpDefinition};
+use crate::config::{CourtConfig, PowerUpConfig};
 use ggez::glam::Vec2;
 use ggez::graphics::Color;
 use ggez::{graphics, Context, GameResult};
-use rand::{rng, Rng};
+use rand::Rng;
 use std::time::Duration;
 
-/// Different types of power-ups that can appear in the game
-#[derive(Debug, Clone, Copy, PartialEq)]
-pub enum PowerUpType {
-    PaddleGrow,     // Increases paddle size
-    PaddleShrink,   // Decreases opponent's paddle size
-    SpeedUp,        // Increases ball speed
-    SpeedDown,      // Decreases ball speed
-    MultiballSplit, // Splits the ball into two
-}
-
-impl PowerUpType {
-    /// Returns a random power-up type
-    pub fn random() -> Self {
-        let mut rng = rng();
-        match rng.random_range(0..5) {
-            0 => PowerUpType::PaddleGrow,
-            1 => PowerUpType::PaddleShrink,
-            2 => PowerUpType::SpeedUp,
-            3 => PowerUpType::SpeedDown,
-            _ => PowerUpType::MultiballSplit,
-        }
-    }
-
-    /// Returns the color associated with this power-up type
-    pub fn color(&self) -> Color {
-        match self {
-            PowerUpType::PaddleGrow => Color::new(0.0, 0.8, 0.0, 1.0), // Green
-            PowerUpType::PaddleShrink => Color::new(0.8, 0.0, 0.0, 1.0), // Red
-            PowerUpType::SpeedUp => Color::new(1.0, 0.5, 0.0, 1.0),    // Orange
-            PowerUpType::SpeedDown => Color::new(0.0, 0.0, 0.8, 1.0),  // Blue
-            PowerUpType::MultiballSplit => Color::new(0.8, 0.0, 0.8, 1.0), // Purple
-        }
-    }
-
-    // /// Returns a string describing the power-up effect
-    // pub fn description(&self) -> &'static str {
-    //     match self {
-    //         PowerUpType::PaddleGrow => "Paddle Growth",
-    //         PowerUpType::PaddleShrink => "Opponent Shrink",
-    //         PowerUpType::SpeedUp => "Speed Up",
-    //         PowerUpType::SpeedDown => "Speed Down",
-    //         PowerUpType::MultiballSplit => "Multiball",
-    //     }
-    // }
-}
-
 /// A power-up that can be collected by either player
 pub struct PowerUp {
     pub position: Vec2,
-    pub power_type: PowerUpType,
+    pub definition: usize, // Index into the manager's power-up definitions
+    pub color: Color,
     pub size: f32,
     pub active: bool,
     pub collected_by: Option<usize>, // 0 for left player, 1 for right player
-    pub remaining_duration: Option<Duration>,
-    pub rotation: f32, // For visual effect
+    pub rotation: f32,               // For visual effect
 }
 
 impl PowerUp {
     /// Creates a new power-up
-    pub fn new(position: Vec2, power_type: PowerUpType) -> Self {
+    pub fn new(position: Vec2, definition: usize, color: Color, size: f32) -> Self {
         Self {
             position,
-            power_type,
-            size: POWERUP_SIZE,
+            definition,
+            color,
+            size,
             active: true,
             collected_by: None,
-            remaining_duration: None,
             rotation: 0.0,
         }
     }
 
-    /// Creates a new random power-up at a random position
-    pub fn random() -> Self {
-        let mut rng = rng();
+    /// Creates a power-up picked by spawn weight at a random position. Returns None if
+    /// no power-up can spawn.
+    pub fn random(
+        rng: &mut impl Rng,
+        definitions: &[PowerUpDefinition],
+        config: &PowerUpConfig,
+        court: &CourtConfig,
+    ) -> Option<Self> {
+        let index = definition::pick(definitions, rng)?;
+        let size = config.size;
 
         // Generate a random position (avoid edges and center line)
         let x = if rng.random_bool(0.5) {
             // Left side
-            rng.random_range(POWERUP_SIZE * 2.0..SCREEN_SIZE.0 / 2.0 - POWERUP_SIZE * 2.0)
+            rng.random_range(size * 2.0..court.width / 2.0 - size * 2.0)
         } else {
             // Right side
-            rng.random_range(
-                SCREEN_SIZE.0 / 2.0 + POWERUP_SIZE * 2.0..SCREEN_SIZE.0 - POWERUP_SIZE * 2.0,
-            )
+            rng.random_range(court.width / 2.0 + size * 2.0..court.width - size * 2.0)
         };
 
-        let y = rng.random_range(POWERUP_SIZE * 2.0..SCREEN_SIZE.1 - POWERUP_SIZE * 2.0);
+        let y = rng.random_range(size * 2.0..court.height - size * 2.0);
 
-        Self::new(Vec2::new(x, y), PowerUpType::random())
+        Some(Self::new(
+            Vec2::new(x, y),
+            index,
+            definitions[index].color,
+            size,
+        ))
     }
 
     /// Updates the power-up state
-    pub fn update(&mut self, delta: Duration) -> bool {
+    pub fn update(&mut self, delta: Duration) {
         // Rotate the power-up for visual effect
         self.rotation += 1.0 * delta.as_secs_f32();
         if self.rotation > std::f32::consts::TAU {
             self.rotation -= std::f32::consts::TAU;
         }
-
-        // If the power-up is collected, update its duration
-        if let Some(duration) = &mut self.remaining_duration {
-            *duration = duration.saturating_sub(delta);
-
-            // Check if the power-up effect has expired
-            if duration.as_secs_f32() <= 0.0 {
-                return false; // Power-up expired
-            }
-        }
-
-        true // Power-up still active
     }
 
-    /// Activates the power-up effect when collected
+    /// Marks the power-up as collected. Its effect is run by the manager.
     pub fn activate(&mut self, player_index: usize) {
         self.active = false;
         self.collected_by = Some(player_index);
-        self.remaining_duration = Some(Duration::from_secs_f32(POWERUP_DURATION));
-    }
-
-    /// Checks if the power-up should flash (about to expire)
-    pub fn should_flash(&self) -> bool {
-        if let Some(duration) = self.remaining_duration {
-            return duration.as_secs_f32() <= POWERUP_FLASH_THRESHOLD;
-        }
-        false
-    }
-
-    /// Gets the current opacity based on flashing state
-    pub fn get_opacity(&self, total_time: f32) -> f32 {
-        if self.should_flash() {
-            // Flash by alternating opacity based on time
-            let flash_cycle = (total_time * POWERUP_FLASH_SPEED).sin() * 0.5 + 0.5;
-            return 0.5 + flash_cycle * 0.5; // Oscillate between 0.5 and 1.0
-        }
-        1.0 // Full opacity
     }
 
     /// Draws the power-up
-    pub fn draw(
-        &self,
-        ctx: &mut Context,
-        canvas: &mut graphics::Canvas,
-        total_time: f32,
-    ) -> GameResult {
+    pub fn draw(&self, ctx: &mut Context, canvas: &mut graphics::Canvas) -> GameResult {
         if !self.active {
             return Ok(());
         }
 
-        let mut color = self.power_type.color();
-        color.a = self.get_opacity(total_time);
+        let color = self.color;
 
         // Draw a diamond shape for the power-up
         let points = [
@@ -0,0 +1,25 @@
+use crate::entities::side::Side;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+/// The random number generator behind every random decision in a match
+pub type GameRng = StdRng;
+
+/// Creates a game RNG from the given seed
+pub fn seeded(seed: u64) -> GameRng {
+    StdRng::seed_from_u64(seed)
+}
+
+/// Derives a seed for something that belongs to one side, such as a computer player,
+/// so the two sides don't make the same random draws
+pub fn side_seed(seed: u64, side: Side) -> u64 {
+    match side {
+        Side::Left => seed ^ 0x6c65_6674,
+        Side::Right => seed ^ 0x7269_6768,
+    }
+}
+
+/// Picks a fresh seed for a match when the player didn't ask for one
+pub fn random_seed() -> u64 {
+    rand::random()
+}
@@ -0,0 +1,327 @@
+use crate::entities::side::Side;
+use std::time::Duration;
+
+/// Best-of-N sets, each won by the first player to take `games_per_set` games
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SetRules {
+    pub best_of: u32,
+    pub games_per_set: u32,
+}
+
+impl SetRules {
+    /// Sets a player needs to win the match
+    pub fn sets_to_win(&self) -> u32 {
+        self.best_of / 2 + 1
+    }
+}
+
+/// How a match is won
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct MatchRules {
+    /// Points needed to win a game. Without a limit the game never ends on points.
+    pub points_to_win: Option<u32>,
+
+    /// A game is only won with a lead of at least two points
+    pub win_by_two: bool,
+
+    /// Play sets of games instead of a single game
+    pub sets: Option<SetRules>,
+
+    /// When time runs out the leader wins, or the next point wins if the score is tied
+    pub time_limit: Option<Duration>,
+}
+
+/// Where a match currently stands
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatchState {
+    InProgress,
+
+    /// Time ran out with the score tied, so the next point wins (golden goal)
+    Overtime,
+
+    Over {
+        winner: Side,
+    },
+}
+
+/// Points, games and sets won by one player
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, PartialOrd, Ord)]
+pub struct Tally {
+    // Field order matters: comparing tallies ranks sets first, then games, then points
+    pub sets: u32,
+    pub games: u32,
+    pub points: u32,
+}
+
+/// The running score of a match under a set of rules
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MatchScore {
+    pub yanga: Tally,
+    pub simba: Tally,
+    pub state: MatchState,
+}
+
+impl Default for MatchScore {
+    fn default() -> Self {
+        Self {
+            yanga: Tally::default(),
+            simba: Tally::default(),
+            state: MatchState::InProgress,
+        }
+    }
+}
+
+impl MatchScore {
+    /// The tally of the player defending `side`
+    pub fn tally(&self, side: Side) -> &Tally {
+        match side {
+            Side::Left => &self.yanga,
+            Side::Right => &self.simba,
+        }
+    }
+
+    fn tally_mut(&mut self, side: Side) -> &mut Tally {
+        match side {
+            Side::Left => &mut self.yanga,
+            Side::Right => &mut self.simba,
+        }
+    }
+
+    /// Whether the match has been decided
+    pub fn is_over(&self) -> bool {
+        matches!(self.state, MatchState::Over { .. })
+    }
+
+    /// The winner, once the match is over
+    pub fn winner(&self) -> Option<Side> {
+        match self.state {
+            MatchState::Over { winner } => Some(winner),
+            _ => None,
+        }
+    }
+
+    /// Adds points for `side`, rolling them up into games, sets and the match as the rules say
+    pub fn add_points(&mut self, side: Side, points: u32, rules: &MatchRules) {
+        if self.is_over() {
+            return;
+        }
+
+        // Golden goal: the first point in overtime takes the match
+        if self.state == MatchState::Overtime {
+            self.tally_mut(side).points += points;
+            self.state = MatchState::Over { winner: side };
+            return;
+        }
+
+        self.tally_mut(side).points += points;
+
+        let Some(points_to_win) = rules.points_to_win else {
+            return;
+        };
+
+        let own = self.tally(side).points;
+        let other = self.tally(side.opponent()).points;
+        let lead_needed = if rules.win_by_two { 2 } else { 1 };
+        if own < points_to_win || own < other + lead_needed {
+            return;
+        }
+
+        // Game won
+        let Some(sets) = rules.sets else {
+            self.state = MatchState::Over { winner: side };
+            return;
+        };
+
+        self.yanga.points = 0;
+        self.simba.points = 0;
+        self.tally_mut(side).games += 1;
+
+        if self.tally(side).games < sets.games_per_set {
+            return;
+        }
+
+        // Set won
+        self.yanga.games = 0;
+        self.simba.games = 0;
+        self.tally_mut(side).sets += 1;
+
+        if self.tally(side).sets >= sets.sets_to_win() {
+            self.state = MatchState::Over { winner: side };
+        }
+    }
+
+    /// Ends the match or starts overtime once `elapsed` play time passes the time limit
+    pub fn check_time_limit(&mut self, elapsed: Duration, rules: &MatchRules) {
+        let Some(time_limit) = rules.time_limit else {
+            return;
+        };
+        if self.state != MatchState::InProgress || elapsed < time_limit {
+            return;
+        }
+
+        self.state = match self.yanga.cmp(&self.simba) {
+            std::cmp::Ordering::Greater => MatchState::Over { winner: Side::Left },
+            std::cmp::Ordering::Less => MatchState::Over {
+                winner: Side::Right,
+            },
+            std::cmp::Ordering::Equal => MatchState::Overtime,
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn first_to(points: u32) -> MatchRules {
+        MatchRules {
+            points_to_win: Some(points),
+            ..MatchRules::default()
+        }
+    }
+
+    // Scores one point at a time for each side in turn, as written
+    fn play(score: &mut MatchScore, rules: &MatchRules, points: &[Side]) {
+        for &side in points {
+            score.add_points(side, 1, rules);
+        }
+    }
+
+    #[test]
+    fn first_to_the_target_wins() {
+        let rules = first_to(3);
+        let mut score = MatchScore::default();
+
+        play(&mut score, &rules, &[Side::Left, Side::Right, Side::Left]);
+        assert_eq!(score.winner(), None);
+
+        play(&mut score, &rules, &[Side::Left]);
+        assert_eq!(score.winner(), Some(Side::Left));
+
+        // Nothing changes once the match is over
+        play(&mut score, &rules, &[Side::Right]);
+        assert_eq!(score.simba.points, 1);
+    }
+
+    #[test]
+    fn win_by_two_plays_on_past_the_target() {
+        let rules = MatchRules {
+            win_by_two: true,
+            ..first_to(3)
+        };
+        let mut score = MatchScore::default();
+
+        // 3-2 isn't enough, and neither is 4-3
+        play(
+            &mut score,
+            &rules,
+            &[Side::Left, Side::Left, Side::Right, Side::Right, Side::Left],
+        );
+        assert_eq!(score.winner(), None);
+        play(&mut score, &rules, &[Side::Right, Side::Left]);
+        assert_eq!(score.winner(), None);
+
+        play(&mut score, &rules, &[Side::Left]);
+        assert_eq!(score.winner(), Some(Side::Left));
+        assert_eq!((score.yanga.points, score.simba.points), (5, 3));
+    }
+
+    #[test]
+    fn games_roll_over_into_sets() {
+        let rules = MatchRules {
+            sets: Some(SetRules {
+                best_of: 3,
+                games_per_set: 2,
+            }),
+            ..first_to(1)
+        };
+        let mut score = MatchScore::default();
+
+        // A game resets the points, a set resets the games
+        play(&mut score, &rules, &[Side::Right]);
+        assert_eq!(
+            score.simba,
+            Tally {
+                sets: 0,
+                games: 1,
+                points: 0
+            }
+        );
+        play(&mut score, &rules, &[Side::Right]);
+        assert_eq!(
+            score.simba,
+            Tally {
+                sets: 1,
+                games: 0,
+                points: 0
+            }
+        );
+
+        play(&mut score, &rules, &[Side::Left, Side::Left]);
+        assert_eq!(score.yanga.sets, 1);
+        assert_eq!(score.winner(), None);
+
+        // A second set wins a best of three
+        play(&mut score, &rules, &[Side::Left, Side::Right, Side::Left]);
+        assert_eq!(score.winner(), Some(Side::Left));
+    }
+
+    #[test]
+    fn time_limit_goes_to_the_leader() {
+        let rules = MatchRules {
+            time_limit: Some(Duration::from_secs(60)),
+            ..MatchRules::default()
+        };
+        let mut score = MatchScore::default();
+        play(&mut score, &rules, &[Side::Right]);
+
+        score.check_time_limit(Duration::from_secs(59), &rules);
+        assert_eq!(score.state, MatchState::InProgress);
+
+        score.check_time_limit(Duration::from_secs(60), &rules);
+        assert_eq!(score.winner(), Some(Side::Right));
+    }
+
+    #[test]
+    fn tied_at_the_time_limit_the_next_point_wins() {
+        let rules = MatchRules {
+            time_limit: Some(Duration::from_secs(60)),
+            ..first_to(10)
+        };
+        let mut score = MatchScore::default();
+        play(&mut score, &rules, &[Side::Left, Side::Right]);
+
+        score.check_time_limit(Duration::from_secs(61), &rules);
+        assert_eq!(score.state, MatchState::Overtime);
+
+        play(&mut score, &rules, &[Side::Right]);
+        assert_eq!(score.winner(), Some(Side::Right));
+    }
+
+    #[test]
+    fn time_limit_ties_are_broken_by_sets_then_games_then_points() {
+        let rules = MatchRules {
+            time_limit: Some(Duration::from_secs(1)),
+            ..MatchRules::default()
+        };
+        let at_time = |yanga: Tally, simba: Tally| {
+            let mut score = MatchScore {
+                yanga,
+                simba,
+                ..MatchScore::default()
+            };
+            score.check_time_limit(Duration::from_secs(1), &rules);
+            score.winner()
+        };
+
+        let tally = |sets, games, points| Tally {
+            sets,
+            games,
+            points,
+        };
+        assert_eq!(at_time(tally(1, 0, 0), tally(0, 5, 9)), Some(Side::Left));
+        assert_eq!(at_time(tally(1, 1, 0), tally(1, 2, 0)), Some(Side::Right));
+        assert_eq!(at_time(tally(1, 2, 4), tally(1, 2, 3)), Some(Side::Left));
+        assert_eq!(at_time(tally(1, 2, 3), tally(1, 2, 3)), None);
+    }
+}
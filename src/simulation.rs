@@ -0,0 +1,858 @@
+use crate::config::{invalid, ConfigError, GameConfig};
+use crate::entities::{ball::Ball, player::Player, side::Side};
+use crate::game_mode::GameMode;
+use crate::match_config::{BallValues, MatchConfig, MultiballScoring, ServeRule};
+use crate::physics::{self, Collider, Contact, Surface};
+use crate::powerups::manager::PowerUpManager;
+use crate::rng::{self, GameRng};
+use crate::rules::MatchScore;
+use ggez::glam::Vec2;
+use ggez::graphics::{Color, Rect};
+use rand::Rng;
+use std::time::Duration;
+
+/// One paddle's input for a single simulation step
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct PaddleInput {
+    pub movement: f32,       // From -1 (full speed up) to 1 (full speed down)
+    pub target: Option<f32>, // Where the paddle's center should head instead, e.g. the mouse
+    pub release: bool,       // Let go of a ball the paddle is holding
+}
+
+/// Paddle input for a single simulation step
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct FrameInput {
+    pub left: PaddleInput,
+    pub right: PaddleInput,
+}
+
+impl FrameInput {
+    pub fn side(&self, side: Side) -> &PaddleInput {
+        match side {
+            Side::Left => &self.left,
+            Side::Right => &self.right,
+        }
+    }
+
+    pub fn side_mut(&mut self, side: Side) -> &mut PaddleInput {
+        match side {
+            Side::Left => &mut self.left,
+            Side::Right => &mut self.right,
+        }
+    }
+}
+
+/// Something that happened during a simulation step that a frontend may want to react to
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GameEvent {
+    /// A ball bounced off the top or bottom wall
+    WallHit { position: Vec2 },
+
+    /// A ball bounced off a paddle
+    PaddleHit { side: Side, position: Vec2 },
+
+    /// The player on `side` let go of a ball they were holding, to serve or after catching it
+    Served { side: Side },
+
+    /// The paddle on `side` caught a ball with a catch power-up and is holding it
+    Caught { side: Side, position: Vec2 },
+
+    /// Two balls bounced off each other during multiball
+    BallHit { position: Vec2 },
+
+    /// The shield on `side`'s goal line stopped a ball and broke
+    ShieldBroken { side: Side, position: Vec2 },
+
+    /// A ball went out and the player on `side` scored its points
+    Scored { side: Side, points: u32 },
+
+    /// The round is over and the court was reset for a new one
+    RoundOver,
+
+    /// The match rules were met and the player on `winner` won the match
+    MatchOver { winner: Side },
+}
+
+/// The game rules and physics, stepped forward without a window or renderer.
+pub struct Simulation {
+    pub yanga_player: Player,
+    pub simba_player: Player,
+    pub balls: Vec<Ball>, // Now we support multiple balls
+    pub score: MatchScore,
+    // Power-ups
+    pub power_up_manager: PowerUpManager,
+    // Tuning values and match options
+    pub game_config: GameConfig,
+    pub config: MatchConfig,
+    pub rally_count: i32,      // For RallyFever mode
+    pub balls_this_round: u32, // Balls put in play since the round started, removed ones too
+    pub game_time: f32,        // Total game time for Accelerating mode
+    // Serving
+    pub first_server: Side,
+    pub goals: u32,                // Balls scored so far in the match
+    pub last_scorer: Option<Side>, // Who scored the most recent goal
+    // Randomness: every random decision in the match comes from this seeded RNG
+    pub seed: u64,
+    rng: GameRng,
+}
+
+impl Simulation {
+    // Creates a new simulation with the ball ready in the center.
+    // The same seed, settings and inputs always play out the same match.
+    pub fn new(game_config: GameConfig, config: MatchConfig, seed: u64) -> Self {
+        let court = game_config.court;
+        let paddle = game_config.paddle;
+
+        let yanga_player = Player::new(
+            Color::new(0.20, 0.64, 0.31, 1.0),
+            Vec2::new(paddle.padding, court.height / 2.0 - paddle.height / 2.0),
+            &paddle,
+            court.height,
+        );
+
+        let simba_player = Player::new(
+            Color::new(0.74, 0.13, 0.19, 1.0),
+            Vec2::new(
+                court.width - paddle.width - paddle.padding,
+                court.height / 2.0 - paddle.height / 2.0,
+            ),
+            &paddle,
+            court.height,
+        );
+
+        let mut simulation = Simulation {
+            yanga_player,
+            simba_player,
+            balls: Vec::new(),
+            score: MatchScore::default(),
+            power_up_manager: PowerUpManager::new(game_config.power_ups, court),
+            game_config,
+            config,
+            rally_count: 0,
+            balls_this_round: 0,
+            game_time: 0.0,
+            first_server: Side::Left,
+            goals: 0,
+            last_scorer: None,
+            seed,
+            rng: rng::seeded(seed),
+        };
+
+        simulation.toss_for_serve();
+        simulation.start_new_round();
+
+        simulation
+    }
+
+    // Resets paddles, power-ups and balls for a new round, keeping the score
+    pub fn start_new_round(&mut self) {
+        // Reset players and power-ups
+        self.yanga_player.reset_power_ups();
+        self.simba_player.reset_power_ups();
+        self.power_up_manager.reset();
+
+        // Reset balls
+        self.balls.clear();
+        self.balls.push(Ball::new(
+            self.court_center(),
+            &self.game_config.ball,
+            &mut self.rng,
+        ));
+
+        // The server holds the ball until they release it
+        if let Some(side) = self.server() {
+            let paddle = self.player(side).rect();
+            self.balls[0].hold_on(paddle, side, 0.0, true);
+        }
+
+        // Reset game mode specific counters
+        self.rally_count = 0;
+        self.balls_this_round = 1;
+    }
+
+    // The middle of the court, where new balls start
+    fn court_center(&self) -> Vec2 {
+        Vec2::new(
+            self.game_config.court.width / 2.0,
+            self.game_config.court.height / 2.0,
+        )
+    }
+
+    // Picks who serves first when the serve rules need it
+    fn toss_for_serve(&mut self) {
+        if self.config.serve_rule != ServeRule::Random {
+            self.first_server = if self.rng.random_bool(0.5) {
+                Side::Left
+            } else {
+                Side::Right
+            };
+        }
+    }
+
+    // Returns who serves the next round, or `None` for a random launch from the center
+    pub fn server(&self) -> Option<Side> {
+        match self.config.serve_rule {
+            ServeRule::Random => None,
+            ServeRule::LoserServes => Some(
+                self.last_scorer
+                    .map_or(self.first_server, |scorer| scorer.opponent()),
+            ),
+            ServeRule::WinnerServes => Some(self.last_scorer.unwrap_or(self.first_server)),
+            ServeRule::Alternate { every } => {
+                let turns = self.goals / every.max(1);
+                if turns.is_multiple_of(2) {
+                    Some(self.first_server)
+                } else {
+                    Some(self.first_server.opponent())
+                }
+            }
+        }
+    }
+
+    // Clears the score and the clock and starts the match over with the same rules
+    pub fn restart_match(&mut self) {
+        self.score = MatchScore::default();
+        self.game_time = 0.0;
+        self.goals = 0;
+        self.last_scorer = None;
+        self.toss_for_serve();
+        self.start_new_round();
+    }
+
+    // Swaps in new tuning values without restarting the match. The court size can't change
+    // mid-match, so settings with a different court are refused and the current ones kept.
+    pub fn apply_game_config(&mut self, game_config: GameConfig) -> Result<(), ConfigError> {
+        if game_config.court != self.game_config.court {
+            return Err(invalid(
+                "court",
+                "can't change size while the game runs, restart to use the new size",
+            ));
+        }
+
+        self.yanga_player.apply_config(&game_config.paddle);
+        self.simba_player.apply_config(&game_config.paddle);
+        for ball in &mut self.balls {
+            ball.apply_config(&game_config.ball);
+        }
+        self.power_up_manager.apply_config(game_config.power_ups);
+
+        self.game_config = game_config;
+        Ok(())
+    }
+
+    // Switches to the given game mode and starts a fresh round
+    pub fn set_game_mode(&mut self, game_mode: GameMode) {
+        self.config.game_mode = game_mode;
+        self.start_new_round();
+    }
+
+    // Returns the player defending the given side
+    pub fn player(&self, side: Side) -> &Player {
+        match side {
+            Side::Left => &self.yanga_player,
+            Side::Right => &self.simba_player,
+        }
+    }
+
+    // Everything a ball can bounce off. Paddles come first so that when a ball is
+    // squeezed against a wall, the wall has the final say and keeps it on the court.
+    fn colliders(&self, delta_secs: f32) -> Vec<Collider> {
+        // The walls reach well past both goal lines so balls never slip around their ends
+        let court = self.game_config.court;
+        let wall_left = -court.width;
+        let wall_width = court.width * 3.0;
+
+        let paddle_velocity = |player: &Player| {
+            if delta_secs > 0.0 {
+                (player.position - player.previous_position) / delta_secs
+            } else {
+                Vec2::ZERO
+            }
+        };
+
+        let mut colliders = vec![
+            Collider {
+                surface: Surface::Paddle(Side::Left),
+                rect: self.yanga_player.rect(),
+                velocity: paddle_velocity(&self.yanga_player),
+            },
+            Collider {
+                surface: Surface::Paddle(Side::Right),
+                rect: self.simba_player.rect(),
+                velocity: paddle_velocity(&self.simba_player),
+            },
+            Collider {
+                surface: Surface::Wall,
+                rect: Rect::new(wall_left, -court.height, wall_width, court.height),
+                velocity: Vec2::ZERO,
+            },
+            Collider {
+                surface: Surface::Wall,
+                rect: Rect::new(wall_left, court.height, wall_width, court.height),
+                velocity: Vec2::ZERO,
+            },
+        ];
+
+        colliders.extend([Side::Left, Side::Right].into_iter().filter_map(|side| {
+            self.shield(side).map(|rect| Collider {
+                surface: Surface::Shield(side),
+                rect,
+                velocity: Vec2::ZERO,
+            })
+        }));
+        colliders
+    }
+
+    // The bar guarding `side`'s goal line, if a shield power-up put one there
+    pub fn shield(&self, side: Side) -> Option<Rect> {
+        if !self.power_up_manager.has_shield(side.index()) {
+            return None;
+        }
+
+        let court = self.game_config.court;
+        let thickness = self.game_config.power_ups.shield_thickness;
+        let x = match side {
+            Side::Left => 0.0,
+            Side::Right => court.width - thickness,
+        };
+        Some(Rect::new(x, 0.0, thickness, court.height))
+    }
+
+    // Adds a new ball for multiball power-up. With escalating values it is worth one more
+    // than the last ball added this round, even if that one has already gone out.
+    fn add_multiball(&mut self) {
+        self.balls_this_round += 1;
+        if self.balls.is_empty() {
+            // If no balls exist (shouldn't happen, but just in case)
+            self.balls.push(Ball::new(
+                self.court_center(),
+                &self.game_config.ball,
+                &mut self.rng,
+            ));
+        } else {
+            // Create a split from the first ball
+            let mut new_ball = Ball::split_from(&self.balls[0], &mut self.rng);
+            new_ball.points = match self.config.ball_values {
+                BallValues::Uniform => 1,
+                BallValues::Escalating => self.balls_this_round,
+            };
+            self.balls.push(new_ball);
+        }
+    }
+
+    // Bounces every pair of touching balls off each other. A ball held on a paddle stays put,
+    // so balls pass through it rather than knocking it off.
+    fn collide_balls(&mut self, events: &mut Vec<GameEvent>) {
+        for i in 0..self.balls.len() {
+            let (head, tail) = self.balls.split_at_mut(i + 1);
+            let ball = &mut head[i];
+            if ball.hold.is_some() {
+                continue;
+            }
+
+            for other in tail.iter_mut().filter(|other| other.hold.is_none()) {
+                if let Some(position) = ball.collide_with(other) {
+                    events.push(GameEvent::BallHit { position });
+                }
+            }
+        }
+    }
+
+    // Advances the game by `delta` using the given paddle input and returns what happened
+    pub fn step(&mut self, delta: Duration, input: &FrameInput) -> Vec<GameEvent> {
+        let mut events = Vec::new();
+
+        // Nothing moves once the match is decided
+        if self.score.is_over() {
+            return events;
+        }
+
+        // Cap the step to prevent physics issues. A validated config's ticks are never
+        // longer than this, so only callers stepping by hand can hit the cap.
+        let settings = self.game_config;
+        let delta_secs = delta.as_secs_f32().min(settings.simulation.max_delta_time);
+        let delta = Duration::from_secs_f32(delta_secs);
+
+        // Update total game time
+        self.game_time += delta_secs;
+
+        // Update positions of the players based on button press
+        move_paddle(&mut self.yanga_player, &input.left, delta_secs);
+        move_paddle(&mut self.simba_player, &input.right, delta_secs);
+
+        // Update power-ups if enabled
+        if settings.power_ups.enabled {
+            self.power_up_manager.update(
+                delta,
+                &self.balls,
+                &mut self.yanga_player,
+                &mut self.simba_player,
+                &mut self.rng,
+            );
+
+            // Split off another ball when a multiball power-up is collected
+            if self.power_up_manager.take_multiball_request() {
+                self.add_multiball();
+            }
+        }
+
+        // Update each ball
+        let mut colliders = self.colliders(delta_secs);
+        let paddle_rects = [colliders[0].rect, colliders[1].rect];
+        let paddle_rect = |side: Side| paddle_rects[side.index()];
+
+        for ball in &mut self.balls {
+            // A held ball rides along with its paddle until it is released
+            if let Some(hold) = ball.hold {
+                ball.begin_step();
+                ball.follow(paddle_rect(hold.side), delta_secs);
+
+                if input.side(hold.side).release || hold.time >= settings.ball.max_serve_hold_time {
+                    ball.release(
+                        paddle_rect(hold.side),
+                        settings.ball.max_serve_angle_degrees.to_radians(),
+                    );
+                    events.push(GameEvent::Served { side: hold.side });
+                }
+                continue;
+            }
+
+            // Ball speed increases after a long rally in rally fever, and power-ups speed up
+            // or slow down balls on their way to the opponent of whoever collected them
+            let rally_multiplier = if self.config.game_mode == GameMode::RallyFever
+                && self.rally_count >= settings.modes.long_rally_threshold
+            {
+                settings.modes.long_rally_speed_multiplier
+            } else {
+                1.0
+            };
+            let power_up_multiplier = self.power_up_manager.ball_speed_multiplier(ball.velocity.x);
+            ball.apply_speed_multiplier(rally_multiplier * power_up_multiplier);
+
+            // Apply game mode specific logic
+            match self.config.game_mode {
+                GameMode::Accelerating => {
+                    // Gradually accelerate the ball
+                    ball.accelerate(settings.modes.ball_acceleration, delta_secs);
+                }
+                GameMode::RallyFever => {
+                    // The speed change was applied above
+                }
+                GameMode::Classic => {
+                    // Standard behavior, just normalize the velocity
+                    ball.normalize_velocity();
+                }
+            }
+
+            ball.begin_step();
+
+            // A paddle may have moved into the ball, so push it back out first.
+            // It only bounces if it was still heading into whatever it overlapped.
+            let mut overlaps = ball.resolve_overlaps(&colliders);
+            overlaps.extend(
+                colliders
+                    .iter()
+                    .filter(|collider| matches!(collider.surface, Surface::Paddle(_)))
+                    .filter_map(|collider| ball.pop_out_sideways(collider)),
+            );
+            for contact in overlaps {
+                if ball.velocity.dot(contact.normal) < 0.0 {
+                    if catch_ball(ball, &contact, &mut self.power_up_manager, &mut events) {
+                        self.rally_count += 1;
+                        break;
+                    }
+                    self.rally_count += bounce(ball, &contact, settings.ball.speed, &mut events);
+                    break_shield(&contact, &mut colliders, &mut self.power_up_manager);
+                }
+            }
+            if ball.hold.is_some() {
+                continue;
+            }
+
+            // Move the ball, bouncing at the exact moment it touches a wall or paddle
+            // and then spending the rest of the step travelling in the new direction
+            let mut remaining_time = delta_secs;
+
+            for _ in 0..settings.simulation.max_ball_contacts_per_step {
+                let Some(contact) = ball.sweep(remaining_time, &colliders) else {
+                    break;
+                };
+                remaining_time = contact.remaining_time;
+                if catch_ball(ball, &contact, &mut self.power_up_manager, &mut events) {
+                    self.rally_count += 1;
+                    break;
+                }
+                self.rally_count += bounce(ball, &contact, settings.ball.speed, &mut events);
+                break_shield(&contact, &mut colliders, &mut self.power_up_manager);
+            }
+        }
+
+        // Let balls bounce off each other during multiball
+        if self.config.ball_collisions {
+            self.collide_balls(&mut events);
+        }
+
+        // Handle scoring
+        self.score_balls_out(&mut events);
+
+        // Running out of time either ends the match or starts golden-goal overtime
+        if !self.score.is_over() {
+            self.score
+                .check_time_limit(Duration::from_secs_f32(self.game_time), &self.config.rules);
+            if let Some(winner) = self.score.winner() {
+                events.push(GameEvent::MatchOver { winner });
+            }
+        }
+
+        events
+    }
+
+    // Awards points for balls that left the court and ends the round when the
+    // multiball rules say so. The court is left as it is once the match is over.
+    fn score_balls_out(&mut self, events: &mut Vec<GameEvent>) {
+        let court_width = self.game_config.court.width;
+
+        match self.config.multiball_scoring {
+            MultiballScoring::FirstOut => {
+                // The first ball out scores and the round is over
+                let goal = self.balls.iter().find_map(|ball| {
+                    scoring_side(ball, court_width).map(|side| (side, ball.points))
+                });
+
+                if let Some((side, points)) = goal {
+                    self.award_points(side, points, events);
+                    if !self.score.is_over() {
+                        self.start_new_round();
+                        events.push(GameEvent::RoundOver);
+                    }
+                }
+            }
+            MultiballScoring::Survival => {
+                // Every ball out scores and leaves play, the rest keep going
+                let mut goals = Vec::new();
+                self.balls
+                    .retain(|ball| match scoring_side(ball, court_width) {
+                        Some(side) => {
+                            goals.push((side, ball.points));
+                            false
+                        }
+                        None => true,
+                    });
+
+                for (side, points) in goals {
+                    self.award_points(side, points, events);
+                }
+
+                if self.balls.is_empty() && !self.score.is_over() {
+                    self.start_new_round();
+                    events.push(GameEvent::RoundOver);
+                }
+            }
+        }
+    }
+
+    // Adds points to a player's score and reports the end of the match if they decided it
+    fn award_points(&mut self, side: Side, points: u32, events: &mut Vec<GameEvent>) {
+        if self.score.is_over() {
+            return;
+        }
+
+        self.score.add_points(side, points, &self.config.rules);
+        self.goals += 1;
+        self.last_scorer = Some(side);
+        events.push(GameEvent::Scored { side, points });
+
+        if let Some(winner) = self.score.winner() {
+            events.push(GameEvent::MatchOver { winner });
+        }
+    }
+}
+
+// Returns which player scores if the ball has left the court
+fn scoring_side(ball: &Ball, court_width: f32) -> Option<Side> {
+    if ball.position.x + ball.radius < 0.0 {
+        // Simba scores
+        Some(Side::Right)
+    } else if ball.position.x - ball.radius > court_width {
+        // Yanga scores
+        Some(Side::Left)
+    } else {
+        None
+    }
+}
+
+// Bounces a ball off whatever it touched and records the event.
+// Returns how many paddle hits that was, for counting rallies.
+fn bounce(ball: &mut Ball, contact: &Contact, spin_speed: f32, events: &mut Vec<GameEvent>) -> i32 {
+    match contact.collider.surface {
+        Surface::Wall => {
+            ball.velocity.y = contact.normal.y * ball.velocity.y.abs();
+            events.push(GameEvent::WallHit {
+                position: contact.point,
+            });
+            0
+        }
+        Surface::Paddle(side) => {
+            bounce_off_paddle(ball, &contact.collider, contact.normal, spin_speed);
+            events.push(GameEvent::PaddleHit {
+                side,
+                position: contact.point,
+            });
+            1
+        }
+        Surface::Shield(side) => {
+            // Straight back into play, as if off a wall
+            ball.velocity.x = contact.normal.x * ball.velocity.x.abs();
+            events.push(GameEvent::ShieldBroken {
+                side,
+                position: contact.point,
+            });
+            0
+        }
+    }
+}
+
+// A shield only stops one ball: once hit, it is gone for the other balls this step too
+fn break_shield(contact: &Contact, colliders: &mut Vec<Collider>, power_ups: &mut PowerUpManager) {
+    if let Surface::Shield(side) = contact.collider.surface {
+        colliders.retain(|collider| collider.surface != contact.collider.surface);
+        power_ups.break_shield(side.index());
+    }
+}
+
+// A paddle with a catch running holds a ball that hits its face instead of sending it back.
+// Returns whether the ball was caught.
+fn catch_ball(
+    ball: &mut Ball,
+    contact: &Contact,
+    power_ups: &mut PowerUpManager,
+    events: &mut Vec<GameEvent>,
+) -> bool {
+    let Surface::Paddle(side) = contact.collider.surface else {
+        return false;
+    };
+    let front_face = match side {
+        Side::Left => Vec2::X,
+        Side::Right => Vec2::NEG_X,
+    };
+    if contact.normal != front_face || !power_ups.use_catch(side.index()) {
+        return false;
+    }
+
+    ball.catch_on(contact.collider.rect, side);
+    events.push(GameEvent::Caught {
+        side,
+        position: contact.point,
+    });
+    true
+}
+
+// Bounces a ball off a paddle depending on which part of it was hit. Face hits get up to
+// half of `spin_speed` of extra vertical speed.
+fn bounce_off_paddle(ball: &mut Ball, paddle: &Collider, normal: Vec2, spin_speed: f32) {
+    if normal.y == 0.0 {
+        // Front (or back) face: reflect, adding spin depending on how far
+        // from the paddle's center the ball landed
+        ball.velocity = physics::reflect(ball.velocity, normal);
+
+        let paddle_center = paddle.rect.y + paddle.rect.h / 2.0;
+        let distance_from_center = ball.position.y - paddle_center;
+        let normalized_distance = distance_from_center / (paddle.rect.h / 2.0);
+
+        ball.velocity.y += normalized_distance * spin_speed * 0.5; // Add spin effect
+    } else if normal.x == 0.0 {
+        // Top or bottom edge: send the ball away vertically, carrying the paddle's
+        // own speed if it was swinging into the ball so it can't catch up again
+        let paddle_push = (paddle.velocity.y * normal.y).max(0.0);
+        ball.velocity.y = normal.y * (ball.velocity.y.abs() + paddle_push);
+    } else {
+        // Corner: reflect along the contact normal
+        ball.velocity = physics::reflect(ball.velocity, normal);
+    }
+}
+
+// Moves a paddle as its input asks: toward a target if it has one, otherwise at a speed
+fn move_paddle(player: &mut Player, input: &PaddleInput, delta_time: f32) {
+    match input.target {
+        Some(target_y) => player.move_toward(target_y, delta_time),
+        None => player.update(input.movement, delta_time),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::BALL_SPEED;
+    use crate::entities::ball::Hold;
+
+    // A simulation without power-ups, so nothing but the game mode changes the ball's speed
+    fn simulation(game_mode: GameMode) -> Simulation {
+        let mut game_config = GameConfig::default();
+        game_config.power_ups.enabled = false;
+        let config = MatchConfig {
+            game_mode,
+            ..MatchConfig::default()
+        };
+        Simulation::new(game_config, config, 1)
+    }
+
+    #[test]
+    fn accelerating_mode_speeds_up_the_ball_at_the_configured_tick_rate() {
+        let mut simulation = simulation(GameMode::Accelerating);
+        let tick_rate = simulation.game_config.simulation.tick_rate;
+        let tick = Duration::from_secs_f64(1.0 / f64::from(tick_rate));
+        let start_speed = simulation.balls[0].velocity.length();
+
+        // Half a second, so the ball is still crossing the court
+        for _ in 0..tick_rate / 2 {
+            simulation.step(tick, &FrameInput::default());
+        }
+
+        let expected = simulation.game_config.modes.ball_acceleration * 0.5;
+        let gained = simulation.balls[0].velocity.length() - start_speed;
+        assert!(
+            (gained - expected).abs() < expected * 0.1,
+            "gained {} px/s, expected about {}",
+            gained,
+            expected
+        );
+    }
+
+    #[test]
+    fn escalating_values_keep_climbing_after_a_ball_goes_out() {
+        let mut simulation = Simulation::new(
+            GameConfig::default(),
+            MatchConfig {
+                multiball_scoring: MultiballScoring::Survival,
+                ball_values: BallValues::Escalating,
+                ..MatchConfig::default()
+            },
+            1,
+        );
+
+        simulation.add_multiball();
+        simulation.add_multiball();
+        let points: Vec<u32> = simulation.balls.iter().map(|ball| ball.points).collect();
+        assert_eq!(points, [1, 2, 3]);
+
+        // The 3-point ball scores and leaves, so the next one is worth 4, not 3 again
+        simulation.balls.pop();
+        simulation.add_multiball();
+        assert_eq!(simulation.balls.last().map(|ball| ball.points), Some(4));
+
+        // A new round starts the count over
+        simulation.start_new_round();
+        simulation.add_multiball();
+        assert_eq!(simulation.balls.last().map(|ball| ball.points), Some(2));
+    }
+
+    // Everything about a match that the seed could change, recorded tick by tick
+    #[derive(Debug, PartialEq)]
+    struct Replay {
+        balls: Vec<Vec<(Vec2, Vec2)>>, // Position and velocity of each ball
+        power_ups: Vec<Vec<(Vec2, usize)>>, // Position and definition of each power-up
+        score: MatchScore,
+    }
+
+    // Plays a match with power-ups spawning every second, both paddles sweeping up and
+    // down and serving now and then
+    fn replay(seed: u64) -> Replay {
+        let mut game_config = GameConfig::default();
+        game_config.power_ups.spawn_interval = 1.0;
+        let mut simulation = Simulation::new(game_config, MatchConfig::default(), seed);
+        let tick = Duration::from_secs_f64(1.0 / f64::from(game_config.simulation.tick_rate));
+
+        let mut replay = Replay {
+            balls: Vec::new(),
+            power_ups: Vec::new(),
+            score: MatchScore::default(),
+        };
+        for step in 0..600 {
+            let sweep = (step as f32 / 40.0).sin();
+            let input = FrameInput {
+                left: PaddleInput {
+                    movement: sweep,
+                    release: step % 90 == 0,
+                    ..PaddleInput::default()
+                },
+                right: PaddleInput {
+                    movement: -sweep,
+                    release: step % 70 == 0,
+                    ..PaddleInput::default()
+                },
+            };
+            simulation.step(tick, &input);
+
+            replay.balls.push(
+                simulation
+                    .balls
+                    .iter()
+                    .map(|ball| (ball.position, ball.velocity))
+                    .collect(),
+            );
+            replay.power_ups.push(
+                simulation
+                    .power_up_manager
+                    .power_ups()
+                    .iter()
+                    .map(|power_up| (power_up.position, power_up.definition))
+                    .collect(),
+            );
+        }
+        replay.score = simulation.score;
+        replay
+    }
+
+    #[test]
+    fn same_seed_and_input_play_out_the_same_match() {
+        let first = replay(42);
+        assert!(
+            first
+                .power_ups
+                .iter()
+                .any(|power_ups| !power_ups.is_empty()),
+            "power-ups should have spawned"
+        );
+        assert_eq!(first, replay(42));
+        assert_ne!(first, replay(43));
+    }
+
+    #[test]
+    fn a_free_ball_passes_through_a_held_one() {
+        let mut simulation = simulation(GameMode::Classic);
+        let held = simulation.balls[0].position;
+        simulation.balls[0].hold = Some(Hold {
+            side: Side::Left,
+            offset: 0.0,
+            rolls: false,
+            time: 0.0,
+            caught: true,
+        });
+        let mut free = Ball::new(held, &simulation.game_config.ball, &mut rng::seeded(1));
+        free.velocity = Vec2::new(-300.0, 0.0);
+        simulation.balls.push(free);
+
+        let mut events = Vec::new();
+        simulation.collide_balls(&mut events);
+
+        assert!(events.is_empty());
+        assert_eq!(simulation.balls[0].position, held);
+        assert_eq!(simulation.balls[1].velocity, Vec2::new(-300.0, 0.0));
+    }
+
+    #[test]
+    fn reloaded_settings_cannot_resize_the_court() {
+        let mut simulation = simulation(GameMode::Classic);
+        let mut game_config = simulation.game_config;
+        game_config.ball.speed = 500.0;
+        game_config.ball.max_speed = 900.0;
+        game_config.court.height = 400.0;
+
+        assert!(simulation.apply_game_config(game_config).is_err());
+        assert_eq!(simulation.game_config.ball.speed, BALL_SPEED);
+
+        game_config.court = simulation.game_config.court;
+        assert!(simulation.apply_game_config(game_config).is_ok());
+        assert_eq!(simulation.game_config.ball.speed, 500.0);
+    }
+}
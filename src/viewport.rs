@@ -0,0 +1,45 @@
+use ggez::glam::Vec2;
+use ggez::graphics::Rect;
+
+/// Fits the logical court into a window of any size. The court keeps its shape and is
+/// centered, with bars filling the rest of the window.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Viewport {
+    pub court: Vec2,  // Court size in game units
+    pub window: Vec2, // Window size in pixels
+}
+
+impl Viewport {
+    pub fn new(court: Vec2, window: Vec2) -> Self {
+        Self { court, window }
+    }
+
+    // Window pixels per game unit
+    pub fn scale(&self) -> f32 {
+        if self.court.x <= 0.0 || self.court.y <= 0.0 {
+            return 1.0;
+        }
+        (self.window.x / self.court.x).min(self.window.y / self.court.y)
+    }
+
+    // The area in game units that the whole window shows, for `Canvas::set_screen_coordinates`
+    pub fn screen_coordinates(&self) -> Rect {
+        let scale = self.scale();
+        if scale <= 0.0 {
+            return Rect::new(0.0, 0.0, self.court.x, self.court.y);
+        }
+
+        let visible = self.window / scale;
+        let margin = (visible - self.court) / 2.0;
+        Rect::new(-margin.x, -margin.y, visible.x, visible.y)
+    }
+
+    // Converts a point in window pixels, such as the mouse position, to game units
+    pub fn to_court(&self, window_point: Vec2) -> Vec2 {
+        let area = self.screen_coordinates();
+        Vec2::new(
+            area.x + window_point.x / self.window.x.max(1.0) * area.w,
+            area.y + window_point.y / self.window.y.max(1.0) * area.h,
+        )
+    }
+}